@@ -0,0 +1,574 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! An asynchronous wrapper around `DB`, available with the `async` feature.
+//!
+//! Every operation runs on a dedicated `BlockingPool` and is returned as a
+//! `Task`, a future that resolves to the operation's result once it has
+//! completed, or to an error if it panicked. Tasks do not depend on any
+//! particular executor, so they can be awaited from tokio, async-std or any
+//! other runtime.
+
+use ffi;
+use futures_core::Stream;
+use {ColumnFamily, DBRawIterator, Direction, Error, IteratorMode, ReadOptions, WriteBatch, DB};
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// A pool of threads that runs blocking RocksDB operations for an `AsyncDB`.
+///
+/// A pool can be shared between several `AsyncDB`s by cloning it. Its threads
+/// exit once the pool and all `AsyncDB`s using it have been dropped and the
+/// operations already submitted have completed.
+#[derive(Clone)]
+pub struct BlockingPool {
+    sender: Arc<Mutex<Sender<Job>>>,
+}
+
+impl BlockingPool {
+    /// Creates a pool with `threads` worker threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero or if a thread cannot be spawned.
+    pub fn new(threads: usize) -> BlockingPool {
+        assert!(threads > 0, "a blocking pool needs at least one thread");
+
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..threads {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("rocksdb-blocking-{}", i))
+                .spawn(move || loop {
+                    let job = match receiver.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => return,
+                    };
+                    // Jobs catch their own panics, but a worker thread must survive anything.
+                    let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                })
+                .expect("Could not spawn RocksDB blocking pool thread");
+        }
+
+        BlockingPool {
+            sender: Arc::new(Mutex::new(sender)),
+        }
+    }
+
+    /// Runs `f` on the pool, returning a future that resolves to its result.
+    ///
+    /// If `f` panics, the task resolves to an error carrying the panic message.
+    /// Returns an error if the pool's threads have all exited.
+    pub fn spawn<F, T>(&self, f: F) -> Result<Task<T>, Error>
+    where
+        F: FnOnce() -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let shared = Arc::new(Mutex::new(TaskState {
+            result: None,
+            finished: false,
+            waker: None,
+        }));
+        let completer = Completer {
+            shared: shared.clone(),
+        };
+        let job: Job = Box::new(move || {
+            let result = match panic::catch_unwind(AssertUnwindSafe(f)) {
+                Ok(result) => result,
+                Err(payload) => Err(Error::new(format!(
+                    "RocksDB blocking task panicked: {}",
+                    panic_message(&*payload)
+                ))),
+            };
+            completer.complete(result);
+        });
+        match self.sender.lock().unwrap().send(job) {
+            Ok(()) => Ok(Task { shared }),
+            Err(_) => Err(Error::new("RocksDB blocking pool has shut down".to_owned())),
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+struct TaskState<T> {
+    result: Option<Result<T, Error>>,
+    finished: bool,
+    waker: Option<Waker>,
+}
+
+// Completes a task when dropped, so that a job that never runs does not leave the task pending.
+struct Completer<T> {
+    shared: Arc<Mutex<TaskState<T>>>,
+}
+
+impl<T> Completer<T> {
+    fn complete(self, result: Result<T, Error>) {
+        self.shared.lock().unwrap().result = Some(result);
+    }
+}
+
+impl<T> Drop for Completer<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut state = match self.shared.lock() {
+                Ok(state) => state,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            state.finished = true;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A future resolving to the result of an operation run on a `BlockingPool`.
+///
+/// The operation runs whether or not the task is awaited. The task resolves to
+/// an error if the operation fails or panics, or if it could not be run at all.
+pub struct Task<T> {
+    shared: Arc<Mutex<TaskState<T>>>,
+}
+
+impl<T> Task<T> {
+    // A task that has already completed with `result`.
+    fn ready(result: Result<T, Error>) -> Task<T> {
+        Task {
+            shared: Arc::new(Mutex::new(TaskState {
+                result: Some(result),
+                finished: true,
+                waker: None,
+            })),
+        }
+    }
+}
+
+impl<T> Future for Task<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T, Error>> {
+        let mut state = self.shared.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            return Poll::Ready(result);
+        }
+        if state.finished {
+            return Poll::Ready(Err(Error::new(
+                "RocksDB blocking task did not complete".to_owned(),
+            )));
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// A `DB` whose operations run on a `BlockingPool` instead of blocking the
+/// calling thread.
+///
+/// Column families are referred to by name, and keys and values are moved to
+/// the pool, so they must be owned.
+///
+/// ```
+/// use rocksdb::{AsyncDB, BlockingPool, DB, Options};
+/// use std::sync::Arc;
+///
+/// let path = "_path_for_async_db";
+/// {
+///     let db = Arc::new(DB::open_default(path).unwrap());
+///     let async_db = AsyncDB::new(db, BlockingPool::new(2));
+///
+///     let put = async_db.put(b"my key".to_vec(), b"my value".to_vec());
+///     let get = async_db.get(b"my key".to_vec());
+///     // `put` and `get` are futures to be awaited from an async context.
+///     # drop((put, get));
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct AsyncDB {
+    db: Arc<DB>,
+    pool: BlockingPool,
+}
+
+impl AsyncDB {
+    /// Wraps `db`, running its operations on `pool`.
+    pub fn new(db: Arc<DB>, pool: BlockingPool) -> AsyncDB {
+        AsyncDB { db, pool }
+    }
+
+    /// Returns the wrapped database, for operations without an asynchronous variant.
+    pub fn db(&self) -> &Arc<DB> {
+        &self.db
+    }
+
+    fn run<F, T>(&self, f: F) -> Task<T>
+    where
+        F: FnOnce(&DB) -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let db = self.db.clone();
+        self.pool
+            .spawn(move || f(&db))
+            .unwrap_or_else(|e| Task::ready(Err(e)))
+    }
+
+    pub fn get<K>(&self, key: K) -> Task<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]> + Send + 'static,
+    {
+        self.run(move |db| db.get(key))
+    }
+
+    pub fn get_cf<K>(&self, cf: &str, key: K) -> Task<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]> + Send + 'static,
+    {
+        let cf = cf.to_owned();
+        self.run(move |db| with_cf(db, &cf, |cf| db.get_cf(cf, key)))
+    }
+
+    /// Return the values associated with the given keys, in the same order as the keys.
+    pub fn multi_get<K>(&self, keys: Vec<K>) -> Task<Vec<Result<Option<Vec<u8>>, Error>>>
+    where
+        K: AsRef<[u8]> + Send + 'static,
+    {
+        self.run(move |db| Ok(db.multi_get(keys)))
+    }
+
+    pub fn put<K, V>(&self, key: K, value: V) -> Task<()>
+    where
+        K: AsRef<[u8]> + Send + 'static,
+        V: AsRef<[u8]> + Send + 'static,
+    {
+        self.run(move |db| db.put(key, value))
+    }
+
+    pub fn put_cf<K, V>(&self, cf: &str, key: K, value: V) -> Task<()>
+    where
+        K: AsRef<[u8]> + Send + 'static,
+        V: AsRef<[u8]> + Send + 'static,
+    {
+        let cf = cf.to_owned();
+        self.run(move |db| with_cf(db, &cf, |cf| db.put_cf(cf, key, value)))
+    }
+
+    pub fn delete<K>(&self, key: K) -> Task<()>
+    where
+        K: AsRef<[u8]> + Send + 'static,
+    {
+        self.run(move |db| db.delete(key))
+    }
+
+    pub fn delete_cf<K>(&self, cf: &str, key: K) -> Task<()>
+    where
+        K: AsRef<[u8]> + Send + 'static,
+    {
+        let cf = cf.to_owned();
+        self.run(move |db| with_cf(db, &cf, |cf| db.delete_cf(cf, key)))
+    }
+
+    pub fn write(&self, batch: WriteBatch) -> Task<()> {
+        self.run(move |db| db.write(batch))
+    }
+
+    /// Flush database memtable to SST files on disk.
+    pub fn flush(&self) -> Task<()> {
+        self.run(|db| db.flush())
+    }
+
+    pub fn compact_range<S, E>(&self, start: Option<S>, end: Option<E>) -> Task<()>
+    where
+        S: AsRef<[u8]> + Send + 'static,
+        E: AsRef<[u8]> + Send + 'static,
+    {
+        self.run(move |db| {
+            db.compact_range(start, end);
+            Ok(())
+        })
+    }
+
+    /// Opens an iterator over the database that reads entries from the pool
+    /// in batches of `batch_size`.
+    pub fn iterator(&self, mode: IteratorMode, batch_size: usize) -> AsyncDBIterator {
+        self.iterator_opt(mode, ReadOptions::default(), batch_size)
+    }
+
+    /// Opens an iterator over the database with the given read options, such as
+    /// iterate bounds or an `OwnedSnapshot`, reading entries in batches of `batch_size`.
+    pub fn iterator_opt(
+        &self,
+        mode: IteratorMode,
+        readopts: ReadOptions,
+        batch_size: usize,
+    ) -> AsyncDBIterator {
+        let iter = unsafe {
+            let inner = ffi::rocksdb_create_iterator(self.db.inner, readopts.inner);
+            DBRawIterator::from_inner(inner, readopts)
+        };
+        self.batched_iterator(iter, mode, batch_size)
+    }
+
+    /// Opens an iterator over a column family that reads entries from the pool
+    /// in batches of `batch_size`.
+    pub fn iterator_cf(
+        &self,
+        cf: &str,
+        mode: IteratorMode,
+        batch_size: usize,
+    ) -> Result<AsyncDBIterator, Error> {
+        self.iterator_cf_opt(cf, mode, ReadOptions::default(), batch_size)
+    }
+
+    /// Opens an iterator over a column family with the given read options,
+    /// reading entries in batches of `batch_size`.
+    pub fn iterator_cf_opt(
+        &self,
+        cf: &str,
+        mode: IteratorMode,
+        readopts: ReadOptions,
+        batch_size: usize,
+    ) -> Result<AsyncDBIterator, Error> {
+        let iter = with_cf(&self.db, cf, |cf| unsafe {
+            let inner = ffi::rocksdb_create_iterator_cf(self.db.inner, readopts.inner, cf.inner);
+            Ok(DBRawIterator::from_inner(inner, readopts))
+        })?;
+        Ok(self.batched_iterator(iter, mode, batch_size))
+    }
+
+    fn batched_iterator(
+        &self,
+        iter: DBRawIterator<'static>,
+        mode: IteratorMode,
+        batch_size: usize,
+    ) -> AsyncDBIterator {
+        let (seek, direction) = match mode {
+            IteratorMode::Start => (Seek::First, Direction::Forward),
+            IteratorMode::End => (Seek::Last, Direction::Reverse),
+            IteratorMode::From(key, Direction::Forward) => {
+                (Seek::Key(key.to_vec()), Direction::Forward)
+            }
+            IteratorMode::From(key, Direction::Reverse) => {
+                (Seek::KeyForPrev(key.to_vec()), Direction::Reverse)
+            }
+        };
+
+        AsyncDBIterator {
+            state: Some(IteratorState {
+                iter,
+                _db: self.db.clone(),
+                seek: Some(seek),
+                direction,
+            }),
+            pool: self.pool.clone(),
+            batch_size: batch_size.max(1),
+            buffer: VecDeque::new(),
+            pending: None,
+            done: false,
+        }
+    }
+}
+
+#[cfg(not(feature = "multi-threaded-cf"))]
+fn with_cf<T, F>(db: &DB, name: &str, f: F) -> Result<T, Error>
+where
+    F: FnOnce(&ColumnFamily) -> Result<T, Error>,
+{
+    match db.cf_handle(name) {
+        Some(cf) => f(cf),
+        None => Err(Error::new(format!("Invalid column family: {}", name))),
+    }
+}
+
+// A bound handle does not keep the column family's handle alive after it is dropped.
+#[cfg(feature = "multi-threaded-cf")]
+fn with_cf<T, F>(db: &DB, name: &str, f: F) -> Result<T, Error>
+where
+    F: FnOnce(&ColumnFamily) -> Result<T, Error>,
+{
+    match db.bound_cf_handle(name) {
+        Some(cf) => f(&cf),
+        None => Err(Error::new(format!("Invalid column family: {}", name))),
+    }
+}
+
+type KVEntry = (Box<[u8]>, Box<[u8]>);
+type KVBatch = Vec<KVEntry>;
+type FetchResult = Result<(KVBatch, bool), Error>;
+
+enum Seek {
+    First,
+    Last,
+    Key(Vec<u8>),
+    KeyForPrev(Vec<u8>),
+}
+
+struct IteratorState {
+    // Declared before `_db` so that it is dropped first.
+    iter: DBRawIterator<'static>,
+    // Keeps the database open for as long as `iter` exists.
+    _db: Arc<DB>,
+    seek: Option<Seek>,
+    direction: Direction,
+}
+
+impl IteratorState {
+    // Reads up to `batch_size` entries, returning whether the iterator is exhausted.
+    fn fetch(&mut self, batch_size: usize) -> FetchResult {
+        match self.seek.take() {
+            Some(Seek::First) => self.iter.seek_to_first(),
+            Some(Seek::Last) => self.iter.seek_to_last(),
+            Some(Seek::Key(key)) => self.iter.seek(key),
+            Some(Seek::KeyForPrev(key)) => self.iter.seek_for_prev(key),
+            None => {}
+        }
+
+        let mut batch = Vec::with_capacity(batch_size);
+        while batch.len() < batch_size && self.iter.valid() {
+            if let (Some(key), Some(value)) = (self.iter.key(), self.iter.value()) {
+                batch.push((
+                    key.to_vec().into_boxed_slice(),
+                    value.to_vec().into_boxed_slice(),
+                ));
+            }
+            match self.direction {
+                Direction::Forward => self.iter.next(),
+                Direction::Reverse => self.iter.prev(),
+            }
+        }
+
+        if self.iter.valid() {
+            Ok((batch, false))
+        } else {
+            self.iter.status().map(|_| (batch, true))
+        }
+    }
+}
+
+/// An iterator over an `AsyncDB`, reading entries in batches on its `BlockingPool`.
+///
+/// Entries are returned one by one through `next`, or through the
+/// `futures_core::Stream` implementation.
+pub struct AsyncDBIterator {
+    // `None` while a batch is being fetched.
+    state: Option<IteratorState>,
+    pool: BlockingPool,
+    batch_size: usize,
+    buffer: VecDeque<KVEntry>,
+    pending: Option<Task<(IteratorState, FetchResult)>>,
+    done: bool,
+}
+
+impl AsyncDBIterator {
+    /// Returns a future resolving to the next entry, or `None` once the
+    /// iterator is exhausted.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Next<'_> {
+        Next { iter: self }
+    }
+
+    /// Polls for the next entry, fetching a new batch from the pool if needed.
+    pub fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Result<KVEntry, Error>>> {
+        loop {
+            if let Some(entry) = self.buffer.pop_front() {
+                return Poll::Ready(Some(Ok(entry)));
+            }
+
+            if let Some(ref mut pending) = self.pending {
+                let ready = match Pin::new(pending).poll(cx) {
+                    Poll::Ready(ready) => ready,
+                    Poll::Pending => return Poll::Pending,
+                };
+                self.pending = None;
+                // The iterator is lost if fetching the batch panicked.
+                let result = ready.and_then(|(state, result)| {
+                    self.state = Some(state);
+                    result
+                });
+                match result {
+                    Ok((batch, exhausted)) => {
+                        self.done = exhausted;
+                        self.buffer.extend(batch);
+                        continue;
+                    }
+                    Err(e) => {
+                        self.done = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+            }
+
+            if self.done {
+                return Poll::Ready(None);
+            }
+
+            let mut state = self
+                .state
+                .take()
+                .expect("iterator state is present when no batch is pending");
+            let batch_size = self.batch_size;
+            let task = self.pool.spawn(move || {
+                let result = state.fetch(batch_size);
+                Ok((state, result))
+            });
+            match task {
+                Ok(task) => self.pending = Some(task),
+                Err(e) => {
+                    self.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+    }
+}
+
+impl Stream for AsyncDBIterator {
+    type Item = Result<KVEntry, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        AsyncDBIterator::poll_next(self.get_mut(), cx)
+    }
+}
+
+/// A future resolving to the next entry of an `AsyncDBIterator`.
+pub struct Next<'a> {
+    iter: &'a mut AsyncDBIterator,
+}
+
+impl<'a> Future for Next<'a> {
+    type Output = Option<Result<KVEntry, Error>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.iter.poll_next(cx)
+    }
+}
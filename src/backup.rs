@@ -16,7 +16,7 @@
 use ffi;
 use {Error, DB};
 
-use libc::c_int;
+use libc::{c_int, c_uchar};
 use std::ffi::CString;
 use std::path::Path;
 
@@ -32,6 +32,18 @@ pub struct RestoreOptions {
     inner: *mut ffi::rocksdb_restore_options_t,
 }
 
+/// Information about a backup, as returned by `BackupEngine::get_backup_info`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BackupEngineInfo {
+    /// Time the backup was created, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    pub backup_id: u32,
+    /// Size of the backup in bytes.
+    pub size: u64,
+    /// Number of files in the backup.
+    pub num_files: u32,
+}
+
 impl BackupEngine {
     /// Open a backup engine with the specified options.
     pub fn open<P: AsRef<Path>>(
@@ -69,6 +81,23 @@ impl BackupEngine {
         }
     }
 
+    /// Creates a new backup, flushing the memtables first if `flush_before_backup`
+    /// is true so that the backup does not need to include the WAL files.
+    pub fn create_new_backup_flush(
+        &mut self,
+        db: &DB,
+        flush_before_backup: bool,
+    ) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_backup_engine_create_new_backup_flush(
+                self.inner,
+                db.inner,
+                flush_before_backup as c_uchar,
+            ));
+            Ok(())
+        }
+    }
+
     pub fn purge_old_backups(&mut self, num_backups_to_keep: usize) -> Result<(), Error> {
         unsafe {
             ffi_try!(ffi::rocksdb_backup_engine_purge_old_backups(
@@ -79,6 +108,39 @@ impl BackupEngine {
         }
     }
 
+    /// Checks that the files of the backup with the given id exist and have
+    /// the expected sizes. File contents are not checked.
+    pub fn verify_backup(&self, backup_id: u32) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_backup_engine_verify_backup(
+                self.inner, backup_id,
+            ));
+            Ok(())
+        }
+    }
+
+    /// Returns information about all backups, from oldest to newest.
+    pub fn get_backup_info(&self) -> Vec<BackupEngineInfo> {
+        unsafe {
+            let info = ffi::rocksdb_backup_engine_get_backup_info(self.inner);
+            if info.is_null() {
+                return Vec::new();
+            }
+
+            let count = ffi::rocksdb_backup_engine_info_count(info);
+            let backups = (0..count)
+                .map(|i| BackupEngineInfo {
+                    timestamp: ffi::rocksdb_backup_engine_info_timestamp(info, i),
+                    backup_id: ffi::rocksdb_backup_engine_info_backup_id(info, i),
+                    size: ffi::rocksdb_backup_engine_info_size(info, i),
+                    num_files: ffi::rocksdb_backup_engine_info_number_files(info, i),
+                })
+                .collect();
+            ffi::rocksdb_backup_engine_info_destroy(info);
+            backups
+        }
+    }
+
     /// Restore from the latest backup
     ///
     /// # Arguments
@@ -14,8 +14,14 @@
 //
 
 use ffi;
-use ffi_util::opt_bytes_to_ptr;
-use {ColumnFamily, ColumnFamilyDescriptor, Error, FlushOptions, Options, WriteOptions, DB};
+use ffi_util::{error_message, opt_bytes_to_ptr, raw_data};
+use write_batch_ops::{WriteBatchOp, WriteBatchOps};
+#[cfg(feature = "multi-threaded-cf")]
+use {BoundColumnFamily, SharedColumnFamily};
+use {
+    ColumnFamily, ColumnFamilyDescriptor, CompactOptions, Error, FlushOptions,
+    IngestExternalFileOptions, Options, WriteBatchWithIndex, WriteOptions, DB,
+};
 
 use libc::{self, c_char, c_int, c_uchar, c_void, size_t};
 use std::collections::BTreeMap;
@@ -23,11 +29,17 @@ use std::ffi::{CStr, CString};
 use std::fmt;
 use std::fs;
 use std::marker::PhantomData;
-use std::ops::Deref;
-use std::path::Path;
+use std::ops::{Bound, Deref, RangeBounds};
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::slice;
 use std::str;
+#[cfg(feature = "multi-threaded-cf")]
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+#[cfg(feature = "multi-threaded-cf")]
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
 
 unsafe impl Send for DB {}
 unsafe impl Sync for DB {}
@@ -77,11 +89,16 @@ pub enum DBRecoveryMode {
 /// let _ = DB::destroy(&Options::default(), path);
 /// ```
 pub struct WriteBatch {
-    inner: *mut ffi::rocksdb_writebatch_t,
+    pub(crate) inner: *mut ffi::rocksdb_writebatch_t,
 }
 
 pub struct ReadOptions {
-    inner: *mut ffi::rocksdb_readoptions_t,
+    pub(crate) inner: *mut ffi::rocksdb_readoptions_t,
+    // Keeps the snapshot set with `set_owned_snapshot` alive.
+    owned_snapshot: Option<OwnedSnapshot>,
+    // Buffers referred to by the iterate bounds of `inner`.
+    iterate_lower_bound: Option<Vec<u8>>,
+    iterate_upper_bound: Option<Vec<u8>>,
 }
 
 /// A consistent view of the database at the point of creation.
@@ -158,9 +175,44 @@ unsafe impl<'a> Sync for Snapshot<'a> {}
 /// ```
 pub struct DBRawIterator<'a> {
     inner: *mut ffi::rocksdb_iterator_t,
+    // The iterator refers to the iterate bounds owned by its read options.
+    _readopts: ReadOptions,
     db: PhantomData<&'a DB>,
 }
 
+/// Metadata of an SST file that is part of the database, as returned by `DB::live_files`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveFile {
+    /// Name of the file, relative to the database directory.
+    pub name: String,
+    /// Level of the LSM tree the file belongs to.
+    pub level: i32,
+    /// Size of the file in bytes.
+    pub size: usize,
+    /// Smallest user key in the file.
+    pub start_key: Option<Vec<u8>>,
+    /// Largest user key in the file.
+    pub end_key: Option<Vec<u8>>,
+    /// Number of entries in the file.
+    pub num_entries: u64,
+    /// Number of deletions in the file.
+    pub num_deletions: u64,
+}
+
+/// A key range from `start_key` (inclusive) to `end_key` (exclusive), as used by
+/// `DB::get_approximate_sizes`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Range<'a> {
+    start_key: &'a [u8],
+    end_key: &'a [u8],
+}
+
+impl<'a> Range<'a> {
+    pub fn new(start_key: &'a [u8], end_key: &'a [u8]) -> Range<'a> {
+        Range { start_key, end_key }
+    }
+}
+
 /// Iterates the batches of writes since a given sequence number.
 ///
 /// `DBWALIterator` is returned by `DB::get_updates_since()` and will return the
@@ -275,10 +327,11 @@ pub enum IteratorMode<'a> {
 }
 
 impl<'a> DBRawIterator<'a> {
-    fn new(db: &DB, readopts: &ReadOptions) -> DBRawIterator<'a> {
+    fn new(db: &DB, readopts: ReadOptions) -> DBRawIterator<'a> {
         unsafe {
             DBRawIterator {
                 inner: ffi::rocksdb_create_iterator(db.inner, readopts.inner),
+                _readopts: readopts,
                 db: PhantomData,
             }
         }
@@ -287,16 +340,33 @@ impl<'a> DBRawIterator<'a> {
     fn new_cf(
         db: &DB,
         cf_handle: &ColumnFamily,
-        readopts: &ReadOptions,
+        readopts: ReadOptions,
     ) -> Result<DBRawIterator<'a>, Error> {
         unsafe {
             Ok(DBRawIterator {
                 inner: ffi::rocksdb_create_iterator_cf(db.inner, readopts.inner, cf_handle.inner),
+                _readopts: readopts,
                 db: PhantomData,
             })
         }
     }
 
+    /// Wraps an iterator created by another rocksdb handle, such as a transaction.
+    ///
+    /// # Unsafe
+    /// Requires that the pointer was returned by one of the rocksdb `create_iterator`
+    /// functions with `readopts` and that its source outlives `'a`.
+    pub(crate) unsafe fn from_inner(
+        inner: *mut ffi::rocksdb_iterator_t,
+        readopts: ReadOptions,
+    ) -> DBRawIterator<'a> {
+        DBRawIterator {
+            inner,
+            _readopts: readopts,
+            db: PhantomData,
+        }
+    }
+
     /// Returns `true` if the iterator is valid. An iterator is invalidated when
     /// it reaches the end of its defined range, or when it encounters an error.
     ///
@@ -532,29 +602,30 @@ impl<'a> Drop for DBRawIterator<'a> {
 }
 
 impl<'a> DBIterator<'a> {
-    fn new(db: &DB, readopts: &ReadOptions, mode: IteratorMode) -> DBIterator<'a> {
-        let mut rv = DBIterator {
-            raw: DBRawIterator::new(db, readopts),
-            direction: Direction::Forward, // blown away by set_mode()
-            just_seeked: false,
-        };
-        rv.set_mode(mode);
-        rv
+    fn new(db: &DB, readopts: ReadOptions, mode: IteratorMode) -> DBIterator<'a> {
+        DBIterator::from_raw(DBRawIterator::new(db, readopts), mode)
     }
 
     fn new_cf(
         db: &DB,
         cf_handle: &ColumnFamily,
-        readopts: &ReadOptions,
+        readopts: ReadOptions,
         mode: IteratorMode,
     ) -> Result<DBIterator<'a>, Error> {
+        Ok(DBIterator::from_raw(
+            DBRawIterator::new_cf(db, cf_handle, readopts)?,
+            mode,
+        ))
+    }
+
+    pub(crate) fn from_raw(raw: DBRawIterator<'a>, mode: IteratorMode) -> DBIterator<'a> {
         let mut rv = DBIterator {
-            raw: DBRawIterator::new_cf(db, cf_handle, readopts)?,
+            raw,
             direction: Direction::Forward, // blown away by set_mode()
             just_seeked: false,
         };
         rv.set_mode(mode);
-        Ok(rv)
+        rv
     }
 
     pub fn set_mode(&mut self, mode: IteratorMode) {
@@ -649,7 +720,7 @@ impl<'a> Snapshot<'a> {
 
     pub fn iterator_opt(&self, mode: IteratorMode, mut readopts: ReadOptions) -> DBIterator<'a> {
         readopts.set_snapshot(self);
-        DBIterator::new(self.db, &readopts, mode)
+        DBIterator::new(self.db, readopts, mode)
     }
 
     pub fn iterator_cf_opt(
@@ -659,7 +730,7 @@ impl<'a> Snapshot<'a> {
         mode: IteratorMode,
     ) -> Result<DBIterator, Error> {
         readopts.set_snapshot(self);
-        DBIterator::new_cf(self.db, cf_handle, &readopts, mode)
+        DBIterator::new_cf(self.db, cf_handle, readopts, mode)
     }
 
     /// Opens a raw iterator over the data in this snapshot, using the default read options.
@@ -677,7 +748,7 @@ impl<'a> Snapshot<'a> {
     /// Opens a raw iterator over the data in this snapshot, using the given read options.
     pub fn raw_iterator_opt(&self, mut readopts: ReadOptions) -> DBRawIterator {
         readopts.set_snapshot(self);
-        DBRawIterator::new(self.db, &readopts)
+        DBRawIterator::new(self.db, readopts)
     }
 
     /// Opens a raw iterator over the data in this snapshot under the given column family, using the given read options.
@@ -687,7 +758,7 @@ impl<'a> Snapshot<'a> {
         mut readopts: ReadOptions,
     ) -> Result<DBRawIterator, Error> {
         readopts.set_snapshot(self);
-        DBRawIterator::new_cf(self.db, cf_handle, &readopts)
+        DBRawIterator::new_cf(self.db, cf_handle, readopts)
     }
 
     pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Vec<u8>>, Error> {
@@ -722,6 +793,77 @@ impl<'a> Snapshot<'a> {
         readopts.set_snapshot(self);
         self.db.get_cf_opt(cf, key.as_ref(), &readopts)
     }
+
+    /// Returns the value of `key` in this snapshot, as updated by `batch`.
+    pub fn get_from_batch<K: AsRef<[u8]>>(
+        &self,
+        batch: &WriteBatchWithIndex,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let mut readopts = ReadOptions::default();
+        readopts.set_snapshot(self);
+        batch.get_from_batch_and_db_opt(self.db, key, &readopts)
+    }
+
+    pub fn get_from_batch_cf<K: AsRef<[u8]>>(
+        &self,
+        batch: &WriteBatchWithIndex,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let mut readopts = ReadOptions::default();
+        readopts.set_snapshot(self);
+        batch.get_from_batch_and_db_cf_opt(self.db, cf, key, &readopts)
+    }
+
+    /// Return the values associated with the given keys in this snapshot.
+    pub fn multi_get<K, I>(&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        let readopts = ReadOptions::default();
+        self.multi_get_opt(keys, readopts)
+    }
+
+    /// Return the values associated with the given keys and column families in this snapshot.
+    pub fn multi_get_cf<'c, K, I>(&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        let readopts = ReadOptions::default();
+        self.multi_get_cf_opt(keys, readopts)
+    }
+
+    /// Return the values associated with the given keys in this snapshot using read options.
+    pub fn multi_get_opt<K, I>(
+        &self,
+        keys: I,
+        mut readopts: ReadOptions,
+    ) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        readopts.set_snapshot(self);
+        self.db.multi_get_opt(keys, &readopts)
+    }
+
+    /// Return the values associated with the given keys and column families in this snapshot
+    /// using read options.
+    pub fn multi_get_cf_opt<'c, K, I>(
+        &self,
+        keys: I,
+        mut readopts: ReadOptions,
+    ) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        readopts.set_snapshot(self);
+        self.db.multi_get_cf_opt(keys, &readopts)
+    }
 }
 
 impl<'a> Drop for Snapshot<'a> {
@@ -732,6 +874,401 @@ impl<'a> Drop for Snapshot<'a> {
     }
 }
 
+/// A snapshot that keeps its database alive through an `Arc<DB>`, so that it can
+/// be stored in long-lived structs or sent to other threads.
+///
+/// Cloning an `OwnedSnapshot` is cheap and yields a handle to the same snapshot,
+/// which is released once every clone, and every `ReadOptions` or iterator
+/// created from it, has been dropped.
+///
+/// ```
+/// use rocksdb::{IteratorMode, OwnedSnapshot, Options, ReadOptions, DB};
+/// use std::sync::Arc;
+///
+/// let path = "_path_for_owned_snapshot";
+/// {
+///     let db = Arc::new(DB::open_default(path).unwrap());
+///     db.put(b"k1", b"v1").unwrap();
+///     let snapshot = OwnedSnapshot::new(db.clone());
+///     db.put(b"k1", b"v2").unwrap();
+///
+///     let mut readopts = ReadOptions::default();
+///     readopts.set_owned_snapshot(&snapshot);
+///     assert_eq!(db.get_opt(b"k1", &readopts).unwrap().unwrap(), b"v1");
+///
+///     let iter = snapshot.iterator(IteratorMode::Start);
+///     std::thread::spawn(move || assert_eq!(iter.count(), 1)).join().unwrap();
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+#[derive(Clone)]
+pub struct OwnedSnapshot {
+    inner: Arc<OwnedSnapshotInner>,
+}
+
+struct OwnedSnapshotInner {
+    db: Arc<DB>,
+    inner: *const ffi::rocksdb_snapshot_t,
+    sequence_number: Option<u64>,
+}
+
+/// `Send` and `Sync` implementations for `OwnedSnapshotInner` are safe, because it is
+/// immutable and can be safely shared between threads.
+unsafe impl Send for OwnedSnapshotInner {}
+unsafe impl Sync for OwnedSnapshotInner {}
+
+/// An iterator over the data of an `OwnedSnapshot`, which keeps the snapshot alive.
+pub struct OwnedDBIterator {
+    // Declared first so that it is dropped before the snapshot and the database.
+    iter: DBIterator<'static>,
+    _snapshot: OwnedSnapshot,
+}
+
+/// A raw iterator over the data of an `OwnedSnapshot`, which keeps the snapshot alive.
+///
+/// See `DBRawIterator` for the meaning of each method.
+pub struct OwnedDBRawIterator {
+    // Declared first so that it is dropped before the snapshot and the database.
+    raw: DBRawIterator<'static>,
+    _snapshot: OwnedSnapshot,
+}
+
+impl OwnedSnapshot {
+    /// Creates a snapshot of the current state of `db`.
+    pub fn new(db: Arc<DB>) -> OwnedSnapshot {
+        // The C API does not expose the sequence number of a snapshot, but a
+        // snapshot is taken at the latest sequence number, so it is known when
+        // no write was published while the snapshot was being created.
+        let (inner, sequence_number) = unsafe {
+            let before = ffi::rocksdb_get_latest_sequence_number(db.inner);
+            let inner = ffi::rocksdb_create_snapshot(db.inner);
+            let after = ffi::rocksdb_get_latest_sequence_number(db.inner);
+            (inner, if before == after { Some(before) } else { None })
+        };
+        OwnedSnapshot {
+            inner: Arc::new(OwnedSnapshotInner {
+                db,
+                inner,
+                sequence_number,
+            }),
+        }
+    }
+
+    /// Returns the database this snapshot was taken from.
+    pub fn db(&self) -> &Arc<DB> {
+        &self.inner.db
+    }
+
+    /// Returns the sequence number of the last write visible in this snapshot,
+    /// or `None` if writes were published while the snapshot was created, in
+    /// which case it cannot be determined.
+    pub fn sequence_number(&self) -> Option<u64> {
+        self.inner.sequence_number
+    }
+
+    pub fn iterator(&self, mode: IteratorMode) -> OwnedDBIterator {
+        self.iterator_opt(mode, ReadOptions::default())
+    }
+
+    pub fn iterator_cf(
+        &self,
+        cf_handle: &ColumnFamily,
+        mode: IteratorMode,
+    ) -> Result<OwnedDBIterator, Error> {
+        self.iterator_cf_opt(cf_handle, ReadOptions::default(), mode)
+    }
+
+    pub fn iterator_opt(&self, mode: IteratorMode, readopts: ReadOptions) -> OwnedDBIterator {
+        let raw = self.raw_iterator_opt(readopts);
+        OwnedDBIterator {
+            iter: DBIterator::from_raw(raw.raw, mode),
+            _snapshot: raw._snapshot,
+        }
+    }
+
+    pub fn iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+        mode: IteratorMode,
+    ) -> Result<OwnedDBIterator, Error> {
+        let raw = self.raw_iterator_cf_opt(cf_handle, readopts)?;
+        Ok(OwnedDBIterator {
+            iter: DBIterator::from_raw(raw.raw, mode),
+            _snapshot: raw._snapshot,
+        })
+    }
+
+    pub fn raw_iterator(&self) -> OwnedDBRawIterator {
+        self.raw_iterator_opt(ReadOptions::default())
+    }
+
+    pub fn raw_iterator_cf(&self, cf_handle: &ColumnFamily) -> Result<OwnedDBRawIterator, Error> {
+        self.raw_iterator_cf_opt(cf_handle, ReadOptions::default())
+    }
+
+    pub fn raw_iterator_opt(&self, mut readopts: ReadOptions) -> OwnedDBRawIterator {
+        readopts.set_owned_snapshot(self);
+        OwnedDBRawIterator {
+            raw: DBRawIterator::new(self.static_db(), readopts),
+            _snapshot: self.clone(),
+        }
+    }
+
+    pub fn raw_iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        mut readopts: ReadOptions,
+    ) -> Result<OwnedDBRawIterator, Error> {
+        readopts.set_owned_snapshot(self);
+        Ok(OwnedDBRawIterator {
+            raw: DBRawIterator::new_cf(self.static_db(), cf_handle, readopts)?,
+            _snapshot: self.clone(),
+        })
+    }
+
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Vec<u8>>, Error> {
+        self.get_opt(key, ReadOptions::default())
+    }
+
+    pub fn get_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_cf_opt(cf, key, ReadOptions::default())
+    }
+
+    pub fn get_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        readopts.set_owned_snapshot(self);
+        self.inner.db.get_opt(key.as_ref(), &readopts)
+    }
+
+    pub fn get_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        readopts.set_owned_snapshot(self);
+        self.inner.db.get_cf_opt(cf, key.as_ref(), &readopts)
+    }
+
+    /// Return the values associated with the given keys in this snapshot.
+    pub fn multi_get<K, I>(&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        self.multi_get_opt(keys, ReadOptions::default())
+    }
+
+    /// Return the values associated with the given keys and column families in this snapshot.
+    pub fn multi_get_cf<'c, K, I>(&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        self.multi_get_cf_opt(keys, ReadOptions::default())
+    }
+
+    /// Return the values associated with the given keys in this snapshot using read options.
+    pub fn multi_get_opt<K, I>(
+        &self,
+        keys: I,
+        mut readopts: ReadOptions,
+    ) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        readopts.set_owned_snapshot(self);
+        self.inner.db.multi_get_opt(keys, &readopts)
+    }
+
+    /// Return the values associated with the given keys and column families in this snapshot
+    /// using read options.
+    pub fn multi_get_cf_opt<'c, K, I>(
+        &self,
+        keys: I,
+        mut readopts: ReadOptions,
+    ) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        readopts.set_owned_snapshot(self);
+        self.inner.db.multi_get_cf_opt(keys, &readopts)
+    }
+
+    /// Returns the database with an unbounded lifetime, for iterators that hold
+    /// a clone of this snapshot and therefore keep the database alive.
+    fn static_db(&self) -> &'static DB {
+        unsafe { &*(&*self.inner.db as *const DB) }
+    }
+}
+
+impl Drop for OwnedSnapshotInner {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_release_snapshot(self.db.inner, self.inner);
+        }
+    }
+}
+
+impl OwnedDBIterator {
+    /// See [`set_mode`](DBIterator::set_mode)
+    pub fn set_mode(&mut self, mode: IteratorMode) {
+        self.iter.set_mode(mode)
+    }
+
+    /// See [`valid`](DBRawIterator::valid)
+    pub fn valid(&self) -> bool {
+        self.iter.valid()
+    }
+
+    /// See [`status`](DBRawIterator::status)
+    pub fn status(&self) -> Result<(), Error> {
+        self.iter.status()
+    }
+}
+
+impl Iterator for OwnedDBIterator {
+    type Item = KVBytes;
+
+    fn next(&mut self) -> Option<KVBytes> {
+        self.iter.next()
+    }
+}
+
+impl OwnedDBRawIterator {
+    pub fn valid(&self) -> bool {
+        self.raw.valid()
+    }
+
+    pub fn status(&self) -> Result<(), Error> {
+        self.raw.status()
+    }
+
+    pub fn seek_to_first(&mut self) {
+        self.raw.seek_to_first()
+    }
+
+    pub fn seek_to_last(&mut self) {
+        self.raw.seek_to_last()
+    }
+
+    pub fn seek<K: AsRef<[u8]>>(&mut self, key: K) {
+        self.raw.seek(key)
+    }
+
+    pub fn seek_for_prev<K: AsRef<[u8]>>(&mut self, key: K) {
+        self.raw.seek_for_prev(key)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) {
+        self.raw.next()
+    }
+
+    pub fn prev(&mut self) {
+        self.raw.prev()
+    }
+
+    pub fn key(&self) -> Option<&[u8]> {
+        self.raw.key()
+    }
+
+    pub fn value(&self) -> Option<&[u8]> {
+        self.raw.value()
+    }
+}
+
+/// How a database is opened by `DB::open_cf_descriptors_internal`.
+pub(crate) enum AccessType<'a> {
+    ReadWrite,
+    ReadOnly { error_if_log_file_exist: bool },
+    Secondary { secondary_path: &'a Path },
+}
+
+/// Opens a database with the column families described by `cfs`, always
+/// including the default one, and returns it along with their handles.
+///
+/// `open` makes the FFI call, given the number of column families and arrays of
+/// their names, their options and the handles for RocksDB to fill in. If any
+/// handle comes back null, the others are destroyed and the database is passed
+/// to `close`.
+pub(crate) fn open_cf_handles<T, F>(
+    mut cfs: Vec<ColumnFamilyDescriptor>,
+    open: F,
+    close: unsafe extern "C" fn(*mut T),
+) -> Result<(*mut T, BTreeMap<String, ColumnFamily>), Error>
+where
+    F: FnOnce(
+        c_int,
+        *mut *const c_char,
+        *mut *const ffi::rocksdb_options_t,
+        *mut *mut ffi::rocksdb_column_family_handle_t,
+    ) -> Result<*mut T, Error>,
+{
+    // Always open the default column family.
+    if !cfs.iter().any(|cf| cf.name == "default") {
+        cfs.push(ColumnFamilyDescriptor {
+            name: String::from("default"),
+            options: Options::default(),
+        });
+    }
+    // We need to store our CStrings in an intermediate vector
+    // so that their pointers remain valid.
+    let c_cfs: Vec<CString> = cfs
+        .iter()
+        .map(|cf| CString::new(cf.name.as_bytes()).unwrap())
+        .collect();
+
+    let mut cfnames: Vec<_> = c_cfs.iter().map(|cf| cf.as_ptr()).collect();
+
+    // These handles will be populated by DB.
+    let mut cfhandles: Vec<_> = cfs.iter().map(|_| ptr::null_mut()).collect();
+
+    let mut cfopts: Vec<_> = cfs.iter().map(|cf| cf.options.inner as *const _).collect();
+
+    let db = open(
+        cfs.len() as c_int,
+        cfnames.as_mut_ptr(),
+        cfopts.as_mut_ptr(),
+        cfhandles.as_mut_ptr(),
+    )?;
+
+    if db.is_null() {
+        return Err(Error::new("Could not initialize database.".to_owned()));
+    }
+
+    if cfhandles.iter().any(|handle| handle.is_null()) {
+        unsafe {
+            for handle in cfhandles.into_iter().filter(|handle| !handle.is_null()) {
+                ffi::rocksdb_column_family_handle_destroy(handle);
+            }
+            close(db);
+        }
+        return Err(Error::new(
+            "Received null column family \
+             handle from DB."
+                .to_owned(),
+        ));
+    }
+
+    let cf_map = cfs
+        .into_iter()
+        .zip(cfhandles)
+        .map(|(cf_desc, inner)| (cf_desc.name, ColumnFamily { inner }))
+        .collect();
+    Ok((db, cf_map))
+}
+
 impl ColumnFamilyDescriptor {
     // Create a new column family descriptor with the specified name and options.
     pub fn new<S>(name: S, options: Options) -> Self
@@ -758,6 +1295,38 @@ impl DB {
         DB::open_cf(opts, path, None::<&str>)
     }
 
+    /// Open the database with the specified options, dropping values older than `ttl`
+    /// during compaction.
+    ///
+    /// Expired values are removed lazily, so reads may still return them until
+    /// the files containing them are compacted. The TTL has second granularity,
+    /// so fractions of a second are rounded up, and a TTL of zero never expires
+    /// anything. A TTL above `i32::MAX` seconds is rejected. Values are stored
+    /// with their write time appended, so a database created with this function
+    /// must always be opened with it.
+    pub fn open_with_ttl<P: AsRef<Path>>(
+        opts: &Options,
+        path: P,
+        ttl: Duration,
+    ) -> Result<DB, Error> {
+        let ttl = ttl_secs(ttl)?;
+        let path = path.as_ref();
+        let cpath = to_cpath(path)?;
+        if let Err(e) = fs::create_dir_all(path) {
+            return Err(Error::new(format!(
+                "Failed to create RocksDB directory: `{:?}`.",
+                e
+            )));
+        }
+
+        let db = unsafe { ffi_try!(ffi::rocksdb_open_with_ttl(opts.inner, cpath.as_ptr(), ttl,)) };
+        if db.is_null() {
+            return Err(Error::new("Could not initialize database.".to_owned()));
+        }
+
+        Ok(DB::from_parts(db, BTreeMap::new(), path.to_path_buf()))
+    }
+
     /// Open a database with the given database options and column family names.
     ///
     /// Column families opened using this function will be created with default `Options`.
@@ -776,6 +1345,19 @@ impl DB {
 
     /// Open a database with the given database options and column family descriptors.
     pub fn open_cf_descriptors<P, I>(opts: &Options, path: P, cfs: I) -> Result<DB, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = ColumnFamilyDescriptor>,
+    {
+        DB::open_cf_descriptors_internal(opts, path, cfs, &AccessType::ReadWrite)
+    }
+
+    pub(crate) fn open_cf_descriptors_internal<P, I>(
+        opts: &Options,
+        path: P,
+        cfs: I,
+        access_type: &AccessType,
+    ) -> Result<DB, Error>
     where
         P: AsRef<Path>,
         I: IntoIterator<Item = ColumnFamilyDescriptor>,
@@ -794,81 +1376,145 @@ impl DB {
             }
         };
 
-        if let Err(e) = fs::create_dir_all(&path) {
-            return Err(Error::new(format!(
-                "Failed to create RocksDB directory: `{:?}`.",
-                e
-            )));
+        // Read-only and secondary instances must not create the primary's directory.
+        let dir = match *access_type {
+            AccessType::ReadWrite => Some(path),
+            AccessType::ReadOnly { .. } => None,
+            AccessType::Secondary { secondary_path } => Some(secondary_path),
+        };
+        if let Some(dir) = dir {
+            if let Err(e) = fs::create_dir_all(dir) {
+                return Err(Error::new(format!(
+                    "Failed to create RocksDB directory: `{:?}`.",
+                    e
+                )));
+            }
         }
 
-        let db: *mut ffi::rocksdb_t;
-        let mut cf_map = BTreeMap::new();
+        let csecondary_path = match *access_type {
+            AccessType::Secondary { secondary_path } => Some(to_cpath(secondary_path)?),
+            _ => None,
+        };
 
-        if cfs.is_empty() {
-            unsafe {
-                db = ffi_try!(ffi::rocksdb_open(opts.inner, cpath.as_ptr() as *const _,));
-            }
+        let (db, cf_map) = if cfs.is_empty() {
+            let db = unsafe {
+                match *access_type {
+                    AccessType::ReadWrite => {
+                        ffi_try!(ffi::rocksdb_open(opts.inner, cpath.as_ptr() as *const _,))
+                    }
+                    AccessType::ReadOnly {
+                        error_if_log_file_exist,
+                    } => ffi_try!(ffi::rocksdb_open_for_read_only(
+                        opts.inner,
+                        cpath.as_ptr() as *const _,
+                        error_if_log_file_exist as c_uchar,
+                    )),
+                    AccessType::Secondary { .. } => ffi_try!(ffi::rocksdb_open_as_secondary(
+                        opts.inner,
+                        cpath.as_ptr() as *const _,
+                        csecondary_path.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                    )),
+                }
+            };
+            (db, BTreeMap::new())
         } else {
-            let mut cfs_v = cfs;
-            // Always open the default column family.
-            if !cfs_v.iter().any(|cf| cf.name == "default") {
-                cfs_v.push(ColumnFamilyDescriptor {
-                    name: String::from("default"),
-                    options: Options::default(),
-                });
-            }
-            // We need to store our CStrings in an intermediate vector
-            // so that their pointers remain valid.
-            let c_cfs: Vec<CString> = cfs_v
-                .iter()
-                .map(|cf| CString::new(cf.name.as_bytes()).unwrap())
-                .collect();
-
-            let mut cfnames: Vec<_> = c_cfs.iter().map(|cf| cf.as_ptr()).collect();
-
-            // These handles will be populated by DB.
-            let mut cfhandles: Vec<_> = cfs_v.iter().map(|_| ptr::null_mut()).collect();
+            open_cf_handles(
+                cfs,
+                |num_cfs, cfnames, cfopts, cfhandles| unsafe {
+                    Ok(match *access_type {
+                        AccessType::ReadWrite => ffi_try!(ffi::rocksdb_open_column_families(
+                            opts.inner,
+                            cpath.as_ptr(),
+                            num_cfs,
+                            cfnames,
+                            cfopts,
+                            cfhandles,
+                        )),
+                        AccessType::ReadOnly {
+                            error_if_log_file_exist,
+                        } => ffi_try!(ffi::rocksdb_open_for_read_only_column_families(
+                            opts.inner,
+                            cpath.as_ptr(),
+                            num_cfs,
+                            cfnames,
+                            cfopts,
+                            cfhandles,
+                            error_if_log_file_exist as c_uchar,
+                        )),
+                        AccessType::Secondary { .. } => {
+                            ffi_try!(ffi::rocksdb_open_as_secondary_column_families(
+                                opts.inner,
+                                cpath.as_ptr(),
+                                csecondary_path.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                                num_cfs,
+                                cfnames,
+                                cfopts,
+                                cfhandles,
+                            ))
+                        }
+                    })
+                },
+                ffi::rocksdb_close,
+            )?
+        };
 
-            let mut cfopts: Vec<_> = cfs_v
-                .iter()
-                .map(|cf| cf.options.inner as *const _)
-                .collect();
+        if db.is_null() {
+            return Err(Error::new("Could not initialize database.".to_owned()));
+        }
 
-            unsafe {
-                db = ffi_try!(ffi::rocksdb_open_column_families(
-                    opts.inner,
-                    cpath.as_ptr(),
-                    cfs_v.len() as c_int,
-                    cfnames.as_mut_ptr(),
-                    cfopts.as_mut_ptr(),
-                    cfhandles.as_mut_ptr(),
-                ));
-            }
+        Ok(DB::from_parts(db, cf_map, path.to_path_buf()))
+    }
 
-            for handle in &cfhandles {
-                if handle.is_null() {
-                    return Err(Error::new(
-                        "Received null column family \
-                         handle from DB."
-                            .to_owned(),
-                    ));
-                }
-            }
+    #[cfg(not(feature = "multi-threaded-cf"))]
+    pub(crate) fn from_parts(
+        inner: *mut ffi::rocksdb_t,
+        cfs: BTreeMap<String, ColumnFamily>,
+        path: PathBuf,
+    ) -> DB {
+        DB { inner, cfs, path }
+    }
 
-            for (cf_desc, inner) in cfs_v.iter().zip(cfhandles) {
-                cf_map.insert(cf_desc.name.clone(), ColumnFamily { inner });
-            }
+    #[cfg(feature = "multi-threaded-cf")]
+    pub(crate) fn from_parts(
+        inner: *mut ffi::rocksdb_t,
+        cfs: BTreeMap<String, ColumnFamily>,
+        path: PathBuf,
+    ) -> DB {
+        let cfs = cfs
+            .into_iter()
+            .map(|(name, cf)| {
+                let cf = SharedColumnFamily {
+                    cf,
+                    lent: AtomicBool::new(false),
+                };
+                (name, Arc::new(cf))
+            })
+            .collect();
+        DB {
+            inner,
+            cfs: RwLock::new(cfs),
+            dropped_cfs: Mutex::new(Vec::new()),
+            path,
         }
+    }
 
-        if db.is_null() {
-            return Err(Error::new("Could not initialize database.".to_owned()));
+    /// Destroys the handles of all column families. Must be called exactly once, right
+    /// before the database is closed.
+    #[cfg(not(feature = "multi-threaded-cf"))]
+    pub(crate) unsafe fn destroy_cf_handles(&self) {
+        for cf in self.cfs.values() {
+            ffi::rocksdb_column_family_handle_destroy(cf.inner);
         }
+    }
 
-        Ok(DB {
-            inner: db,
-            cfs: cf_map,
-            path: path.to_path_buf(),
-        })
+    /// Destroys the handles of all column families. Must be called exactly once, right
+    /// before the database is closed.
+    #[cfg(feature = "multi-threaded-cf")]
+    pub(crate) unsafe fn destroy_cf_handles(&self) {
+        // No `BoundColumnFamily` can outlive the database, so this releases the last
+        // reference to every handle.
+        self.cfs.write().unwrap().clear();
+        self.dropped_cfs.lock().unwrap().clear();
     }
 
     pub fn list_cf<P: AsRef<Path>>(opts: &Options, path: P) -> Result<Vec<String>, Error> {
@@ -935,12 +1581,89 @@ impl DB {
         self.write_opt(batch, &WriteOptions::default())
     }
 
+    /// Writes the updates of a `WriteBatchWithIndex` atomically. The batch is
+    /// left untouched, so it can still be read from or cleared afterwards.
+    pub fn write_with_index_opt(
+        &self,
+        batch: &WriteBatchWithIndex,
+        writeopts: &WriteOptions,
+    ) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_write_writebatch_wi(
+                self.inner,
+                writeopts.inner,
+                batch.inner,
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn write_with_index(&self, batch: &WriteBatchWithIndex) -> Result<(), Error> {
+        self.write_with_index_opt(batch, &WriteOptions::default())
+    }
+
     pub fn write_without_wal(&self, batch: WriteBatch) -> Result<(), Error> {
         let mut wo = WriteOptions::new();
         wo.disable_wal(true);
         self.write_opt(batch, &wo)
     }
 
+    /// Adds the given SST files, as written by an `SstFileWriter`, to the default column family.
+    pub fn ingest_external_file<P: AsRef<Path>>(&self, paths: &[P]) -> Result<(), Error> {
+        self.ingest_external_file_opt(paths, &IngestExternalFileOptions::default())
+    }
+
+    /// Adds the given SST files to the default column family with the given options.
+    pub fn ingest_external_file_opt<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+        opts: &IngestExternalFileOptions,
+    ) -> Result<(), Error> {
+        let cpaths = to_cpaths(paths)?;
+        let cpaths: Vec<_> = cpaths.iter().map(|p| p.as_ptr()).collect();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_ingest_external_file(
+                self.inner,
+                cpaths.as_ptr(),
+                cpaths.len() as size_t,
+                opts.inner,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Adds the given SST files, as written by an `SstFileWriter`, to the given column family.
+    pub fn ingest_external_file_cf<P: AsRef<Path>>(
+        &self,
+        cf: &ColumnFamily,
+        paths: &[P],
+    ) -> Result<(), Error> {
+        self.ingest_external_file_cf_opt(cf, paths, &IngestExternalFileOptions::default())
+    }
+
+    /// Adds the given SST files to the given column family with the given options.
+    pub fn ingest_external_file_cf_opt<P: AsRef<Path>>(
+        &self,
+        cf: &ColumnFamily,
+        paths: &[P],
+        opts: &IngestExternalFileOptions,
+    ) -> Result<(), Error> {
+        let cpaths = to_cpaths(paths)?;
+        let cpaths: Vec<_> = cpaths.iter().map(|p| p.as_ptr()).collect();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_ingest_external_file_cf(
+                self.inner,
+                cf.inner,
+                cpaths.as_ptr(),
+                cpaths.len() as size_t,
+                opts.inner,
+            ));
+        }
+        Ok(())
+    }
+
     /// Return the bytes associated with a key value with read options. If you only intend to use
     /// the vector returned temporarily, consider using [`get_pinned_opt`](#method.get_pinned_opt)
     /// to avoid unnecessary memory copy.
@@ -984,6 +1707,103 @@ impl DB {
         self.get_cf_opt(cf, key.as_ref(), &ReadOptions::default())
     }
 
+    /// Return the values associated with the given keys, in the same order as the keys.
+    ///
+    /// All keys are looked up in a single call, which is considerably cheaper than calling
+    /// [`get`](#method.get) for each of them.
+    pub fn multi_get<K, I>(&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        self.multi_get_opt(keys, &ReadOptions::default())
+    }
+
+    /// Return the values associated with the given keys using read options.
+    pub fn multi_get_opt<K, I>(
+        &self,
+        keys: I,
+        readopts: &ReadOptions,
+    ) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        let keys: Vec<K> = keys.into_iter().collect();
+        let (keys_ptrs, keys_sizes): (Vec<_>, Vec<_>) = keys
+            .iter()
+            .map(|k| (k.as_ref().as_ptr() as *const c_char, k.as_ref().len()))
+            .unzip();
+
+        let mut values = vec![ptr::null_mut(); keys.len()];
+        let mut values_sizes = vec![0; keys.len()];
+        let mut errors = vec![ptr::null_mut(); keys.len()];
+        unsafe {
+            ffi::rocksdb_multi_get(
+                self.inner,
+                readopts.inner,
+                keys.len(),
+                keys_ptrs.as_ptr(),
+                keys_sizes.as_ptr(),
+                values.as_mut_ptr(),
+                values_sizes.as_mut_ptr(),
+                errors.as_mut_ptr(),
+            );
+        }
+
+        convert_values(values, values_sizes, errors)
+    }
+
+    /// Return the values associated with the given keys and column families, in the same order
+    /// as the keys.
+    pub fn multi_get_cf<'c, K, I>(&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        self.multi_get_cf_opt(keys, &ReadOptions::default())
+    }
+
+    /// Return the values associated with the given keys and column families using read options.
+    pub fn multi_get_cf_opt<'c, K, I>(
+        &self,
+        keys: I,
+        readopts: &ReadOptions,
+    ) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        let keys: Vec<(&ColumnFamily, K)> = keys.into_iter().collect();
+        let (keys_ptrs, keys_sizes): (Vec<_>, Vec<_>) = keys
+            .iter()
+            .map(|(_, k)| (k.as_ref().as_ptr() as *const c_char, k.as_ref().len()))
+            .unzip();
+        let cfs: Vec<_> = keys
+            .iter()
+            .map(|(cf, _)| cf.inner as *const ffi::rocksdb_column_family_handle_t)
+            .collect();
+
+        let mut values = vec![ptr::null_mut(); keys.len()];
+        let mut values_sizes = vec![0; keys.len()];
+        let mut errors = vec![ptr::null_mut(); keys.len()];
+        unsafe {
+            ffi::rocksdb_multi_get_cf(
+                self.inner,
+                readopts.inner,
+                cfs.as_ptr(),
+                keys.len(),
+                keys_ptrs.as_ptr(),
+                keys_sizes.as_ptr(),
+                values.as_mut_ptr(),
+                values_sizes.as_mut_ptr(),
+                errors.as_mut_ptr(),
+            );
+        }
+
+        convert_values(values, values_sizes, errors)
+    }
+
     /// Return the value associated with a key using RocksDB's PinnableSlice
     /// so as to avoid unnecessary memory copy.
     pub fn get_pinned_opt<K: AsRef<[u8]>>(
@@ -1073,8 +1893,34 @@ impl DB {
         self.get_pinned_cf_opt(cf, key, &ReadOptions::default())
     }
 
+    #[cfg(not(feature = "multi-threaded-cf"))]
     pub fn create_cf<N: AsRef<str>>(&mut self, name: N, opts: &Options) -> Result<(), Error> {
-        let cname = match CString::new(name.as_ref().as_bytes()) {
+        let inner = self.create_cf_inner(name.as_ref(), opts)?;
+        self.cfs
+            .insert(name.as_ref().to_string(), ColumnFamily { inner });
+        Ok(())
+    }
+
+    /// Creates a column family. Other threads may keep using the database and
+    /// its column families while it is created.
+    #[cfg(feature = "multi-threaded-cf")]
+    pub fn create_cf<N: AsRef<str>>(&self, name: N, opts: &Options) -> Result<(), Error> {
+        let mut cfs = self.cfs.write().unwrap();
+        let inner = self.create_cf_inner(name.as_ref(), opts)?;
+        let cf = SharedColumnFamily {
+            cf: ColumnFamily { inner },
+            lent: AtomicBool::new(false),
+        };
+        cfs.insert(name.as_ref().to_string(), Arc::new(cf));
+        Ok(())
+    }
+
+    fn create_cf_inner(
+        &self,
+        name: &str,
+        opts: &Options,
+    ) -> Result<*mut ffi::rocksdb_column_family_handle_t, Error> {
+        let cname = match CString::new(name.as_bytes()) {
             Ok(c) => c,
             Err(_) => {
                 return Err(Error::new(
@@ -1085,18 +1931,15 @@ impl DB {
             }
         };
         unsafe {
-            let inner = ffi_try!(ffi::rocksdb_create_column_family(
+            Ok(ffi_try!(ffi::rocksdb_create_column_family(
                 self.inner,
                 opts.inner,
                 cname.as_ptr(),
-            ));
-
-            self.cfs
-                .insert(name.as_ref().to_string(), ColumnFamily { inner });
-        };
-        Ok(())
+            )))
+        }
     }
 
+    #[cfg(not(feature = "multi-threaded-cf"))]
     pub fn drop_cf(&mut self, name: &str) -> Result<(), Error> {
         if let Some(cf) = self.cfs.remove(name) {
             unsafe {
@@ -1110,22 +1953,85 @@ impl DB {
         }
     }
 
+    /// Drops a column family. Handles to it that are still held elsewhere stay
+    /// valid, but its data can no longer be read or written.
+    ///
+    /// A handle that `cf_handle` has lent out is kept until the database is closed,
+    /// while one only obtained through `bound_cf_handle` is released together with
+    /// its last `BoundColumnFamily`.
+    #[cfg(feature = "multi-threaded-cf")]
+    pub fn drop_cf(&self, name: &str) -> Result<(), Error> {
+        let mut cfs = self.cfs.write().unwrap();
+        let inner = match cfs.get(name) {
+            Some(cf) => cf.cf.inner,
+            None => {
+                return Err(Error::new(
+                    format!("Invalid column family: {}", name).to_owned(),
+                ));
+            }
+        };
+        unsafe {
+            ffi_try!(ffi::rocksdb_drop_column_family(self.inner, inner,));
+        }
+        if let Some(cf) = cfs.remove(name) {
+            // The write lock orders this load after every store made by `cf_handle`.
+            if cf.lent.load(atomic::Ordering::Relaxed) {
+                self.dropped_cfs.lock().unwrap().push(cf);
+            }
+        }
+        Ok(())
+    }
+
     /// Return the underlying column family handle.
+    #[cfg(not(feature = "multi-threaded-cf"))]
     pub fn cf_handle(&self, name: &str) -> Option<&ColumnFamily> {
         self.cfs.get(name)
     }
 
+    /// Return the underlying column family handle.
+    ///
+    /// The handle stays valid until the database is closed, even if its column
+    /// family is dropped in the meantime. Use `bound_cf_handle` for a handle that
+    /// is released as soon as it is no longer used.
+    #[cfg(feature = "multi-threaded-cf")]
+    pub fn cf_handle(&self, name: &str) -> Option<&ColumnFamily> {
+        let cfs = self.cfs.read().unwrap();
+        cfs.get(name).map(|cf| {
+            cf.lent.store(true, atomic::Ordering::Relaxed);
+            // The handle lives behind the `Arc`, which `drop_cf` moves to `dropped_cfs`
+            // now that it is lent, so it is only released when the database is dropped.
+            unsafe { &*(&cf.cf as *const ColumnFamily) }
+        })
+    }
+
+    /// Return a reference-counted handle to a column family.
+    ///
+    /// The handle borrows the database, so it can not outlive it. Once the column
+    /// family is dropped, the handle is released together with its last
+    /// `BoundColumnFamily`.
+    #[cfg(feature = "multi-threaded-cf")]
+    pub fn bound_cf_handle(&self, name: &str) -> Option<BoundColumnFamily> {
+        self.cfs
+            .read()
+            .unwrap()
+            .get(name)
+            .map(|cf| BoundColumnFamily {
+                inner: cf.clone(),
+                db: PhantomData,
+            })
+    }
+
     pub fn iterator<'a, 'b: 'a>(&'a self, mode: IteratorMode) -> DBIterator<'b> {
         let readopts = ReadOptions::default();
-        self.iterator_opt(mode, &readopts)
+        self.iterator_opt(mode, readopts)
     }
 
     pub fn iterator_opt<'a, 'b: 'a>(
         &'a self,
         mode: IteratorMode,
-        readopts: &ReadOptions,
+        readopts: ReadOptions,
     ) -> DBIterator<'b> {
-        DBIterator::new(self, &readopts, mode)
+        DBIterator::new(self, readopts, mode)
     }
 
     /// Opens an iterator using the provided ReadOptions.
@@ -1133,10 +2039,10 @@ impl DB {
     pub fn iterator_cf_opt(
         &self,
         cf_handle: &ColumnFamily,
-        readopts: &ReadOptions,
+        readopts: ReadOptions,
         mode: IteratorMode,
     ) -> Result<DBIterator, Error> {
-        DBIterator::new_cf(self, cf_handle, &readopts, mode)
+        DBIterator::new_cf(self, cf_handle, readopts, mode)
     }
 
     /// Opens an iterator with `set_total_order_seek` enabled.
@@ -1145,7 +2051,7 @@ impl DB {
     pub fn full_iterator<'a, 'b: 'a>(&'a self, mode: IteratorMode) -> DBIterator<'b> {
         let mut opts = ReadOptions::default();
         opts.set_total_order_seek(true);
-        DBIterator::new(self, &opts, mode)
+        DBIterator::new(self, opts, mode)
     }
 
     pub fn prefix_iterator<'a, 'b: 'a, P: AsRef<[u8]>>(&'a self, prefix: P) -> DBIterator<'b> {
@@ -1153,7 +2059,7 @@ impl DB {
         opts.set_prefix_same_as_start(true);
         DBIterator::new(
             self,
-            &opts,
+            opts,
             IteratorMode::From(prefix.as_ref(), Direction::Forward),
         )
     }
@@ -1164,7 +2070,7 @@ impl DB {
         mode: IteratorMode,
     ) -> Result<DBIterator, Error> {
         let opts = ReadOptions::default();
-        DBIterator::new_cf(self, cf_handle, &opts, mode)
+        DBIterator::new_cf(self, cf_handle, opts, mode)
     }
 
     pub fn full_iterator_cf(
@@ -1174,7 +2080,7 @@ impl DB {
     ) -> Result<DBIterator, Error> {
         let mut opts = ReadOptions::default();
         opts.set_total_order_seek(true);
-        DBIterator::new_cf(self, cf_handle, &opts, mode)
+        DBIterator::new_cf(self, cf_handle, opts, mode)
     }
 
     pub fn prefix_iterator_cf<P: AsRef<[u8]>>(
@@ -1187,25 +2093,75 @@ impl DB {
         DBIterator::new_cf(
             self,
             cf_handle,
-            &opts,
+            opts,
             IteratorMode::From(prefix.as_ref(), Direction::Forward),
         )
     }
 
+    /// Opens an iterator over the keys within `range`, in the given direction.
+    ///
+    /// The range is translated into iterate bounds as described in
+    /// `ReadOptions::set_iterate_range`. For a pair of borrowed `Bound`s the key
+    /// type must be given explicitly, as in `db.range_iterator::<&[u8], _>(..)`.
+    ///
+    /// ```
+    /// use rocksdb::{Direction, Options, DB};
+    ///
+    /// let path = "_path_for_rocksdb_range_iterator";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     for key in &["a", "b", "c", "d"] {
+    ///         db.put(key, b"").unwrap();
+    ///     }
+    ///     let keys: Vec<_> = db
+    ///         .range_iterator("b"..="c", Direction::Reverse)
+    ///         .map(|(k, _)| k)
+    ///         .collect();
+    ///     assert_eq!(keys, vec![b"c".to_vec().into_boxed_slice(), b"b".to_vec().into_boxed_slice()]);
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn range_iterator<K, R>(&self, range: R, direction: Direction) -> DBIterator
+    where
+        K: AsRef<[u8]>,
+        R: RangeBounds<K>,
+    {
+        let mut opts = ReadOptions::default();
+        opts.set_iterate_range(range);
+        DBIterator::new(self, opts, bounds_start(direction))
+    }
+
+    /// Opens an iterator over the keys of a column family within `range`, in the given
+    /// direction.
+    pub fn range_iterator_cf<K, R>(
+        &self,
+        cf_handle: &ColumnFamily,
+        range: R,
+        direction: Direction,
+    ) -> Result<DBIterator, Error>
+    where
+        K: AsRef<[u8]>,
+        R: RangeBounds<K>,
+    {
+        let mut opts = ReadOptions::default();
+        opts.set_iterate_range(range);
+        DBIterator::new_cf(self, cf_handle, opts, bounds_start(direction))
+    }
+
     /// Opens a raw iterator over the database, using the default read options
     pub fn raw_iterator(&self) -> DBRawIterator {
         let opts = ReadOptions::default();
-        DBRawIterator::new(self, &opts)
+        DBRawIterator::new(self, opts)
     }
 
     /// Opens a raw iterator over the given column family, using the default read options
     pub fn raw_iterator_cf(&self, cf_handle: &ColumnFamily) -> Result<DBRawIterator, Error> {
         let opts = ReadOptions::default();
-        DBRawIterator::new_cf(self, cf_handle, &opts)
+        DBRawIterator::new_cf(self, cf_handle, opts)
     }
 
     /// Opens a raw iterator over the database, using the given read options
-    pub fn raw_iterator_opt(&self, readopts: &ReadOptions) -> DBRawIterator {
+    pub fn raw_iterator_opt(&self, readopts: ReadOptions) -> DBRawIterator {
         DBRawIterator::new(self, readopts)
     }
 
@@ -1213,7 +2169,7 @@ impl DB {
     pub fn raw_iterator_cf_opt(
         &self,
         cf_handle: &ColumnFamily,
-        readopts: &ReadOptions,
+        readopts: ReadOptions,
     ) -> Result<DBRawIterator, Error> {
         DBRawIterator::new_cf(self, cf_handle, readopts)
     }
@@ -1358,6 +2314,31 @@ impl DB {
         }
     }
 
+    /// Removes the database entries in column family in the range `[from, to)`
+    /// by writing a single range tombstone.
+    pub fn delete_range_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        from: K,
+        to: K,
+        writeopts: &WriteOptions,
+    ) -> Result<(), Error> {
+        let (start_key, end_key) = (from.as_ref(), to.as_ref());
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_delete_range_cf(
+                self.inner,
+                writeopts.inner,
+                cf.inner,
+                start_key.as_ptr() as *const c_char,
+                start_key.len() as size_t,
+                end_key.as_ptr() as *const c_char,
+                end_key.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
     pub fn put<K, V>(&self, key: K, value: V) -> Result<(), Error>
     where
         K: AsRef<[u8]>,
@@ -1374,6 +2355,17 @@ impl DB {
         self.put_cf_opt(cf, key.as_ref(), value.as_ref(), &WriteOptions::default())
     }
 
+    /// Removes the database entries in column family in the range `[from, to)`
+    /// by writing a single range tombstone.
+    pub fn delete_range_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        from: K,
+        to: K,
+    ) -> Result<(), Error> {
+        self.delete_range_cf_opt(cf, from, to, &WriteOptions::default())
+    }
+
     pub fn merge<K, V>(&self, key: K, value: V) -> Result<(), Error>
     where
         K: AsRef<[u8]>,
@@ -1434,6 +2426,104 @@ impl DB {
         }
     }
 
+    /// Compacts the key range from `start` to `end` (both inclusive) with the
+    /// given options. `None` leaves the corresponding end of the range open.
+    pub fn compact_range_opt<S: AsRef<[u8]>, E: AsRef<[u8]>>(
+        &self,
+        start: Option<S>,
+        end: Option<E>,
+        opts: &CompactOptions,
+    ) {
+        unsafe {
+            let start = start.as_ref().map(|s| s.as_ref());
+            let end = end.as_ref().map(|e| e.as_ref());
+
+            ffi::rocksdb_compact_range_opt(
+                self.inner,
+                opts.inner,
+                opt_bytes_to_ptr(start),
+                start.map_or(0, |s| s.len()) as size_t,
+                opt_bytes_to_ptr(end),
+                end.map_or(0, |e| e.len()) as size_t,
+            );
+        }
+    }
+
+    pub fn compact_range_cf_opt<S: AsRef<[u8]>, E: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        start: Option<S>,
+        end: Option<E>,
+        opts: &CompactOptions,
+    ) {
+        unsafe {
+            let start = start.as_ref().map(|s| s.as_ref());
+            let end = end.as_ref().map(|e| e.as_ref());
+
+            ffi::rocksdb_compact_range_cf_opt(
+                self.inner,
+                cf.inner,
+                opts.inner,
+                opt_bytes_to_ptr(start),
+                start.map_or(0, |s| s.len()) as size_t,
+                opt_bytes_to_ptr(end),
+                end.map_or(0, |e| e.len()) as size_t,
+            );
+        }
+    }
+
+    /// Deletes the SST files whose keys all lie in the range from `start` to
+    /// `end` (both inclusive). `None` leaves the corresponding end of the range open.
+    ///
+    /// This reclaims space immediately, without writing tombstones, but keys in
+    /// files that only partially overlap the range, as well as keys in memtables,
+    /// are left in place. Follow up with `delete_range_cf` and a compaction of the
+    /// range to remove them as well. Snapshots do not prevent the files from being
+    /// deleted, so data in the range may disappear from existing snapshots.
+    pub fn delete_files_in_range<S: AsRef<[u8]>, E: AsRef<[u8]>>(
+        &self,
+        start: Option<S>,
+        end: Option<E>,
+    ) -> Result<(), Error> {
+        unsafe {
+            let start = start.as_ref().map(|s| s.as_ref());
+            let end = end.as_ref().map(|e| e.as_ref());
+
+            ffi_try!(ffi::rocksdb_delete_file_in_range(
+                self.inner,
+                opt_bytes_to_ptr(start),
+                start.map_or(0, |s| s.len()) as size_t,
+                opt_bytes_to_ptr(end),
+                end.map_or(0, |e| e.len()) as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    /// Deletes the SST files of column family whose keys all lie in the range
+    /// from `start` to `end` (both inclusive). See `delete_files_in_range`.
+    pub fn delete_files_in_range_cf<S: AsRef<[u8]>, E: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        start: Option<S>,
+        end: Option<E>,
+    ) -> Result<(), Error> {
+        unsafe {
+            let start = start.as_ref().map(|s| s.as_ref());
+            let end = end.as_ref().map(|e| e.as_ref());
+
+            ffi_try!(ffi::rocksdb_delete_file_in_range_cf(
+                self.inner,
+                cf.inner,
+                opt_bytes_to_ptr(start),
+                start.map_or(0, |s| s.len()) as size_t,
+                opt_bytes_to_ptr(end),
+                end.map_or(0, |e| e.len()) as size_t,
+            ));
+            Ok(())
+        }
+    }
+
     pub fn set_options(&self, opts: &[(&str, &str)]) -> Result<(), Error> {
         let copts = opts
             .iter()
@@ -1580,6 +2670,98 @@ impl DB {
         }
     }
 
+    /// Returns the approximate size in bytes of the SST files holding each of
+    /// the given key ranges. Data still in memtables is not counted.
+    pub fn get_approximate_sizes(&self, ranges: &[Range]) -> Vec<u64> {
+        self.get_approximate_sizes_internal(None, ranges)
+    }
+
+    pub fn get_approximate_sizes_cf(&self, cf: &ColumnFamily, ranges: &[Range]) -> Vec<u64> {
+        self.get_approximate_sizes_internal(Some(cf), ranges)
+    }
+
+    fn get_approximate_sizes_internal(
+        &self,
+        cf: Option<&ColumnFamily>,
+        ranges: &[Range],
+    ) -> Vec<u64> {
+        let start_keys: Vec<*const c_char> = ranges
+            .iter()
+            .map(|r| r.start_key.as_ptr() as *const c_char)
+            .collect();
+        let start_key_lens: Vec<size_t> = ranges.iter().map(|r| r.start_key.len()).collect();
+        let end_keys: Vec<*const c_char> = ranges
+            .iter()
+            .map(|r| r.end_key.as_ptr() as *const c_char)
+            .collect();
+        let end_key_lens: Vec<size_t> = ranges.iter().map(|r| r.end_key.len()).collect();
+        let mut sizes: Vec<u64> = vec![0; ranges.len()];
+
+        unsafe {
+            match cf {
+                Some(cf) => ffi::rocksdb_approximate_sizes_cf(
+                    self.inner,
+                    cf.inner,
+                    ranges.len() as c_int,
+                    start_keys.as_ptr(),
+                    start_key_lens.as_ptr(),
+                    end_keys.as_ptr(),
+                    end_key_lens.as_ptr(),
+                    sizes.as_mut_ptr(),
+                ),
+                None => ffi::rocksdb_approximate_sizes(
+                    self.inner,
+                    ranges.len() as c_int,
+                    start_keys.as_ptr(),
+                    start_key_lens.as_ptr(),
+                    end_keys.as_ptr(),
+                    end_key_lens.as_ptr(),
+                    sizes.as_mut_ptr(),
+                ),
+            }
+        }
+        sizes
+    }
+
+    /// Returns the metadata of all SST files of all column families.
+    pub fn live_files(&self) -> Result<Vec<LiveFile>, Error> {
+        unsafe {
+            let files = ffi::rocksdb_livefiles(self.inner);
+            if files.is_null() {
+                return Err(Error::new("Could not get live files.".to_owned()));
+            }
+
+            let count = ffi::rocksdb_livefiles_count(files);
+            let mut live_files = Vec::with_capacity(count as usize);
+            for i in 0..count {
+                let name = CStr::from_ptr(ffi::rocksdb_livefiles_name(files, i))
+                    .to_string_lossy()
+                    .into_owned();
+
+                // The keys point into the live files object, so they are copied
+                // rather than freed.
+                let mut key_len: size_t = 0;
+                let start_key = ffi::rocksdb_livefiles_smallestkey(files, i, &mut key_len);
+                let start_key = copy_bytes(start_key, key_len);
+                let end_key = ffi::rocksdb_livefiles_largestkey(files, i, &mut key_len);
+                let end_key = copy_bytes(end_key, key_len);
+
+                live_files.push(LiveFile {
+                    name,
+                    level: ffi::rocksdb_livefiles_level(files, i) as i32,
+                    size: ffi::rocksdb_livefiles_size(files, i) as usize,
+                    start_key,
+                    end_key,
+                    num_entries: ffi::rocksdb_livefiles_entries(files, i),
+                    num_deletions: ffi::rocksdb_livefiles_deletions(files, i),
+                });
+            }
+            ffi::rocksdb_livefiles_destroy(files);
+
+            Ok(live_files)
+        }
+    }
+
     /// The sequence number of the most recent transaction.
     pub fn latest_sequence_number(&self) -> u64 {
         unsafe { ffi::rocksdb_get_latest_sequence_number(self.inner) }
@@ -1618,35 +2800,30 @@ pub trait WriteBatchIterator {
     fn delete(&mut self, key: Box<[u8]>);
 }
 
-unsafe extern "C" fn writebatch_put_callback(
-    state: *mut c_void,
-    k: *const c_char,
-    klen: usize,
-    v: *const c_char,
-    vlen: usize,
-) {
-    // coerce the raw pointer back into a box, but "leak" it so we prevent
-    // freeing the resource before we are done with it
-    let boxed_cb = Box::from_raw(state as *mut &mut dyn WriteBatchIterator);
-    let leaked_cb = Box::leak(boxed_cb);
-    let key = slice::from_raw_parts(k as *const u8, klen as usize);
-    let value = slice::from_raw_parts(v as *const u8, vlen as usize);
-    leaked_cb.put(
-        key.to_vec().into_boxed_slice(),
-        value.to_vec().into_boxed_slice(),
-    );
-}
-
-unsafe extern "C" fn writebatch_delete_callback(state: *mut c_void, k: *const c_char, klen: usize) {
-    // coerce the raw pointer back into a box, but "leak" it so we prevent
-    // freeing the resource before we are done with it
-    let boxed_cb = Box::from_raw(state as *mut &mut dyn WriteBatchIterator);
-    let leaked_cb = Box::leak(boxed_cb);
-    let key = slice::from_raw_parts(k as *const u8, klen as usize);
-    leaked_cb.delete(key.to_vec().into_boxed_slice());
-}
-
 impl WriteBatch {
+    /// Rebuilds a write batch from the bytes returned by `data`.
+    ///
+    /// Returns an error if `data` is not a well-formed write batch.
+    pub fn from_data(data: &[u8]) -> Result<WriteBatch, Error> {
+        if data.len() < 12 {
+            return Err(Error::new(
+                "Corruption: malformed WriteBatch (too small)".to_owned(),
+            ));
+        }
+        for op in WriteBatchOps::new(data) {
+            op?;
+        }
+
+        unsafe {
+            Ok(WriteBatch {
+                inner: ffi::rocksdb_writebatch_create_from(
+                    data.as_ptr() as *const c_char,
+                    data.len() as size_t,
+                ),
+            })
+        }
+    }
+
     pub fn len(&self) -> usize {
         unsafe { ffi::rocksdb_writebatch_count(self.inner) as usize }
     }
@@ -1664,22 +2841,60 @@ impl WriteBatch {
         self.len() == 0
     }
 
+    /// Returns an iterator over the records of this write batch, including
+    /// merges, range deletions, log data and column family operations.
+    ///
+    /// ```
+    /// use rocksdb::{WriteBatch, WriteBatchOp};
+    ///
+    /// let mut batch = WriteBatch::default();
+    /// batch.put(b"key", b"value").unwrap();
+    /// batch.merge(b"key", b"operand").unwrap();
+    ///
+    /// for op in batch.ops() {
+    ///     match op.unwrap() {
+    ///         WriteBatchOp::Put { cf, key, value } => {
+    ///             assert_eq!(cf, 0);
+    ///             assert_eq!((key, value), (&b"key"[..], &b"value"[..]));
+    ///         }
+    ///         WriteBatchOp::Merge { key, .. } => assert_eq!(key, b"key"),
+    ///         _ => unreachable!(),
+    ///     }
+    /// }
+    /// ```
+    pub fn ops(&self) -> WriteBatchOps {
+        WriteBatchOps::new(self.data())
+    }
+
+    /// Returns the serialized representation of this write batch, from which
+    /// it can be rebuilt with `from_data`.
+    pub fn data(&self) -> &[u8] {
+        unsafe {
+            let mut batch_size: size_t = 0;
+            let data = ffi::rocksdb_writebatch_data(self.inner, &mut batch_size);
+            slice::from_raw_parts(data as *const u8, batch_size as usize)
+        }
+    }
+
     /// Iterate the put and delete operations within this write batch. Note that
     /// this does _not_ return an `Iterator` but instead will invoke the `put()`
     /// and `delete()` member functions of the provided `WriteBatchIterator`
-    /// trait implementation.
+    /// trait implementation for every put and delete on the default column
+    /// family. Other records, such as merges and operations on other column
+    /// families, are skipped; use `ops()` to see them.
     pub fn iterate(&self, callbacks: &mut dyn WriteBatchIterator) {
-        let state = Box::into_raw(Box::new(callbacks));
-        unsafe {
-            ffi::rocksdb_writebatch_iterate(
-                self.inner,
-                state as *mut c_void,
-                Some(writebatch_put_callback),
-                Some(writebatch_delete_callback),
-            );
-            // we must manually set the raw box free since there is no
-            // associated "destroy" callback for this object
-            Box::from_raw(state);
+        // The batch was encoded by RocksDB, so it cannot hold malformed records.
+        for op in self.ops().filter_map(Result::ok) {
+            match op {
+                WriteBatchOp::Put { cf: 0, key, value } => callbacks.put(
+                    key.to_vec().into_boxed_slice(),
+                    value.to_vec().into_boxed_slice(),
+                ),
+                WriteBatchOp::Delete { cf: 0, key } => {
+                    callbacks.delete(key.to_vec().into_boxed_slice())
+                }
+                _ => {}
+            }
         }
     }
 
@@ -1796,6 +3011,41 @@ impl WriteBatch {
         }
     }
 
+    /// Removes the database entry for key, which must have been written at most
+    /// once since it was last deleted. Unlike `delete`, the deletion cancels out
+    /// with the write it removes during compaction, but the result is undefined
+    /// if the key was written more than once, or merged into.
+    pub fn single_delete<K: AsRef<[u8]>>(&mut self, key: K) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_singledelete(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    pub fn single_delete_cf<K: AsRef<[u8]>>(
+        &mut self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_singledelete_cf(
+                self.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
     /// Remove database entries from start key to end key.
     ///
     /// Removes the database entries in the range ["begin_key", "end_key"), i.e.,
@@ -1842,6 +3092,48 @@ impl WriteBatch {
         }
     }
 
+    /// Appends a blob of arbitrary data to the batch. The blob is written to
+    /// the WAL, where it can be read back with `DB::get_updates_since`, but it
+    /// is not applied to the database.
+    pub fn put_log_data<V: AsRef<[u8]>>(&mut self, blob: V) -> Result<(), Error> {
+        let blob = blob.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_put_log_data(
+                self.inner,
+                blob.as_ptr() as *const c_char,
+                blob.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    /// Records the current state of the batch, so that the updates added after
+    /// this point can be undone with `rollback_to_save_point`.
+    pub fn set_save_point(&mut self) {
+        unsafe {
+            ffi::rocksdb_writebatch_set_save_point(self.inner);
+        }
+    }
+
+    /// Removes the updates added since the most recent save point, and removes
+    /// that save point. Returns an error if there is no save point.
+    pub fn rollback_to_save_point(&mut self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_writebatch_rollback_to_save_point(self.inner,));
+        }
+        Ok(())
+    }
+
+    /// Removes the most recent save point without undoing any updates. Returns
+    /// an error if there is no save point.
+    pub fn pop_save_point(&mut self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_writebatch_pop_save_point(self.inner,));
+        }
+        Ok(())
+    }
+
     /// Clear all updates buffered in this batch.
     pub fn clear(&mut self) -> Result<(), Error> {
         unsafe {
@@ -1865,12 +3157,86 @@ impl Drop for WriteBatch {
     }
 }
 
+/// Copies `len` bytes borrowed from RocksDB, without taking ownership of them.
+unsafe fn copy_bytes(ptr: *const c_char, len: size_t) -> Option<Vec<u8>> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(ptr as *const u8, len as usize).to_vec())
+    }
+}
+
+/// Returns the mode starting an iterator at the first key within its bounds in
+/// the given direction.
+fn bounds_start(direction: Direction) -> IteratorMode<'static> {
+    match direction {
+        Direction::Forward => IteratorMode::Start,
+        Direction::Reverse => IteratorMode::End,
+    }
+}
+
+/// Returns the smallest key that is greater than `key` in bytewise order.
+fn next_key(key: &[u8]) -> Vec<u8> {
+    let mut next = Vec::with_capacity(key.len() + 1);
+    next.extend_from_slice(key);
+    next.push(0);
+    next
+}
+
+fn convert_values(
+    values: Vec<*mut c_char>,
+    values_sizes: Vec<size_t>,
+    errors: Vec<*mut c_char>,
+) -> Vec<Result<Option<Vec<u8>>, Error>> {
+    values
+        .into_iter()
+        .zip(values_sizes.into_iter())
+        .zip(errors.into_iter())
+        .map(|((v, s), e)| {
+            if e.is_null() {
+                Ok(unsafe { raw_data(v, s) })
+            } else {
+                Err(Error::new(error_message(e)))
+            }
+        })
+        .collect()
+}
+
+fn to_cpaths<P: AsRef<Path>>(paths: &[P]) -> Result<Vec<CString>, Error> {
+    paths
+        .iter()
+        .map(|path| {
+            CString::new(path.as_ref().to_string_lossy().as_bytes()).map_err(|_| {
+                Error::new(
+                    "Failed to convert path to CString when ingesting external files.".to_owned(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Converts a TTL to the whole seconds taken by the C API, rounding fractions
+/// up so that a sub-second TTL does not turn into "never expire".
+fn ttl_secs(ttl: Duration) -> Result<c_int, Error> {
+    let secs = if ttl.subsec_nanos() > 0 {
+        ttl.as_secs().saturating_add(1)
+    } else {
+        ttl.as_secs()
+    };
+    if secs > c_int::max_value() as u64 {
+        return Err(Error::new(format!(
+            "TTL of {:?} exceeds the maximum of {} seconds.",
+            ttl,
+            c_int::max_value()
+        )));
+    }
+    Ok(secs as c_int)
+}
+
 impl Drop for DB {
     fn drop(&mut self) {
         unsafe {
-            for cf in self.cfs.values() {
-                ffi::rocksdb_column_family_handle_destroy(cf.inner);
-            }
+            self.destroy_cf_handles();
             ffi::rocksdb_close(self.inner);
         }
     }
@@ -1905,21 +3271,99 @@ impl ReadOptions {
         }
     }
 
+    /// Reads from the given snapshot, which these options keep alive until
+    /// they are dropped or another snapshot is set.
+    pub fn set_owned_snapshot(&mut self, snapshot: &OwnedSnapshot) {
+        unsafe {
+            ffi::rocksdb_readoptions_set_snapshot(self.inner, snapshot.inner.inner);
+        }
+        self.owned_snapshot = Some(snapshot.clone());
+    }
+
+    /// Set the lower bound for an iterator.
+    /// The lower bound itself is included on the iteration result.
+    ///
+    /// The options keep a copy of the key, and iterators keep the options they
+    /// were created with, so the bound stays valid as long as it is in use.
+    pub fn set_iterate_lower_bound<K: AsRef<[u8]>>(&mut self, key: K) {
+        let key = key.as_ref().to_vec();
+        unsafe {
+            ffi::rocksdb_readoptions_set_iterate_lower_bound(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+        }
+        self.iterate_lower_bound = Some(key);
+    }
+
     /// Set the upper bound for an iterator.
     /// The upper bound itself is not included on the iteration result.
     ///
-    /// # Safety
+    /// The options keep a copy of the key, and iterators keep the options they
+    /// were created with, so the bound stays valid as long as it is in use.
+    pub fn set_iterate_upper_bound<K: AsRef<[u8]>>(&mut self, key: K) {
+        let key = key.as_ref().to_vec();
+        unsafe {
+            ffi::rocksdb_readoptions_set_iterate_upper_bound(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+        }
+        self.iterate_upper_bound = Some(key);
+    }
+
+    /// Sets the iterate bounds from a Rust range of keys, replacing any bound
+    /// set before. An unbounded side removes the corresponding bound.
+    ///
+    /// Since the lower bound of an iterator is inclusive and its upper bound is
+    /// exclusive, an excluded start or an included end is translated to the key
+    /// followed by a zero byte, which is the next key in bytewise order. This
+    /// is only correct with the default bytewise comparator.
     ///
-    /// This function will store a clone of key and will give a raw pointer of it to the
-    /// underlying C++ API, therefore, when given to any other [`DB`] method you must ensure
-    /// that this [`ReadOptions`] value does not leave the scope too early (e.g. `DB::iterator_cf_opt`).
-    pub unsafe fn set_iterate_upper_bound<K: AsRef<[u8]>>(&mut self, key: K) {
-        let key = key.as_ref();
-        ffi::rocksdb_readoptions_set_iterate_upper_bound(
-            self.inner,
-            key.as_ptr() as *const c_char,
-            key.len() as size_t,
-        );
+    /// The key type cannot be inferred for a pair of borrowed `Bound`s, so it
+    /// must be given explicitly in that case.
+    ///
+    /// ```
+    /// use rocksdb::ReadOptions;
+    /// use std::ops::Bound;
+    ///
+    /// let mut opts = ReadOptions::default();
+    /// opts.set_iterate_range(&b"a"[..]..=&b"c"[..]);
+    /// opts.set_iterate_range::<&[u8], _>((Bound::Excluded(&b"a"[..]), Bound::Unbounded));
+    /// ```
+    pub fn set_iterate_range<K, R>(&mut self, range: R)
+    where
+        K: AsRef<[u8]>,
+        R: RangeBounds<K>,
+    {
+        match range.start_bound() {
+            Bound::Included(key) => self.set_iterate_lower_bound(key),
+            Bound::Excluded(key) => self.set_iterate_lower_bound(next_key(key.as_ref())),
+            Bound::Unbounded => self.clear_iterate_lower_bound(),
+        }
+        match range.end_bound() {
+            Bound::Included(key) => self.set_iterate_upper_bound(next_key(key.as_ref())),
+            Bound::Excluded(key) => self.set_iterate_upper_bound(key),
+            Bound::Unbounded => self.clear_iterate_upper_bound(),
+        }
+    }
+
+    /// Removes the lower bound set for an iterator.
+    pub fn clear_iterate_lower_bound(&mut self) {
+        unsafe {
+            ffi::rocksdb_readoptions_set_iterate_lower_bound(self.inner, ptr::null(), 0);
+        }
+        self.iterate_lower_bound = None;
+    }
+
+    /// Removes the upper bound set for an iterator.
+    pub fn clear_iterate_upper_bound(&mut self) {
+        unsafe {
+            ffi::rocksdb_readoptions_set_iterate_upper_bound(self.inner, ptr::null(), 0);
+        }
+        self.iterate_upper_bound = None;
     }
 
     pub fn set_prefix_same_as_start(&mut self, v: bool) {
@@ -1972,6 +3416,9 @@ impl Default for ReadOptions {
         unsafe {
             ReadOptions {
                 inner: ffi::rocksdb_readoptions_create(),
+                owned_snapshot: None,
+                iterate_lower_bound: None,
+                iterate_upper_bound: None,
             }
         }
     }
@@ -1982,11 +3429,13 @@ impl Default for ReadOptions {
 // rocksdb internally does not rely on thread-local information for its user-exposed types.
 unsafe impl<'a> Send for DBRawIterator<'a> {}
 unsafe impl Send for ReadOptions {}
+unsafe impl Send for WriteBatch {}
 
 // Sync is similarly safe for many types because they do not expose interior mutability, and their
 // use within the rocksdb library is generally behind a const reference
 unsafe impl<'a> Sync for DBRawIterator<'a> {}
 unsafe impl Sync for ReadOptions {}
+unsafe impl Sync for WriteBatch {}
 
 fn to_cpath<P: AsRef<Path>>(path: P) -> Result<CString, Error> {
     match CString::new(path.as_ref().to_string_lossy().as_bytes()) {
@@ -2188,7 +3637,7 @@ fn iterator_test_tailing() {
         let r = db.put(k, v);
         assert!(r.is_ok());
 
-        let tail_iter = db.iterator_opt(IteratorMode::Start, &ro);
+        let tail_iter = db.iterator_opt(IteratorMode::Start, ro);
         for (k, v) in data_iter {
             let r = db.put(k, v);
             assert!(r.is_ok());
@@ -2267,3 +3716,13 @@ fn set_option_test() {
     }
     assert!(DB::destroy(&Options::default(), path).is_ok());
 }
+
+#[test]
+fn ttl_secs_test() {
+    assert_eq!(ttl_secs(Duration::from_secs(0)).unwrap(), 0);
+    assert_eq!(ttl_secs(Duration::from_millis(1)).unwrap(), 1);
+    assert_eq!(ttl_secs(Duration::from_millis(1500)).unwrap(), 2);
+    assert_eq!(ttl_secs(Duration::from_secs(60)).unwrap(), 60);
+    assert!(ttl_secs(Duration::from_secs(c_int::max_value() as u64 + 1)).is_err());
+    assert!(ttl_secs(Duration::from_secs(u64::max_value())).is_err());
+}
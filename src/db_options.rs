@@ -25,9 +25,11 @@ use merge_operator::{
     self, full_merge_callback, partial_merge_callback, MergeFn, MergeOperatorCallback,
 };
 use slice_transform::SliceTransform;
+use statistics::{Histogram, HistogramData, StatisticsSnapshot, Ticker};
 use {
-    BlockBasedIndexType, BlockBasedOptions, DBCompactionStyle, DBCompressionType, DBRecoveryMode,
-    FlushOptions, MemtableFactory, Options, PlainTableFactoryOptions, WriteOptions,
+    BlockBasedIndexType, BlockBasedOptions, BottommostLevelCompaction, Cache, CompactOptions,
+    DBCompactionStyle, DBCompressionType, DBRecoveryMode, FlushOptions, IngestExternalFileOptions,
+    MemtableFactory, Options, PlainTableFactoryOptions, RateLimiter, WriteOptions,
 };
 
 pub fn new_cache(capacity: size_t) -> *mut ffi::rocksdb_cache_t {
@@ -40,11 +42,19 @@ pub fn new_cache(capacity: size_t) -> *mut ffi::rocksdb_cache_t {
 unsafe impl Send for Options {}
 unsafe impl Send for WriteOptions {}
 unsafe impl Send for BlockBasedOptions {}
+unsafe impl Send for IngestExternalFileOptions {}
+unsafe impl Send for RateLimiter {}
+unsafe impl Send for Cache {}
+unsafe impl Send for CompactOptions {}
 // Sync is similarly safe for many types because they do not expose interior mutability, and their
 // use within the rocksdb library is generally behind a const reference
 unsafe impl Sync for Options {}
 unsafe impl Sync for WriteOptions {}
 unsafe impl Sync for BlockBasedOptions {}
+unsafe impl Sync for IngestExternalFileOptions {}
+unsafe impl Sync for RateLimiter {}
+unsafe impl Sync for Cache {}
+unsafe impl Sync for CompactOptions {}
 
 impl Drop for Options {
     fn drop(&mut self) {
@@ -70,6 +80,38 @@ impl Drop for FlushOptions {
     }
 }
 
+impl Drop for Cache {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_cache_destroy(self.inner);
+        }
+    }
+}
+
+impl Drop for RateLimiter {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_ratelimiter_destroy(self.inner);
+        }
+    }
+}
+
+impl Drop for IngestExternalFileOptions {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_ingestexternalfileoptions_destroy(self.inner);
+        }
+    }
+}
+
+impl Drop for CompactOptions {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_compactoptions_destroy(self.inner);
+        }
+    }
+}
+
 impl Drop for WriteOptions {
     fn drop(&mut self) {
         unsafe {
@@ -94,6 +136,30 @@ impl BlockBasedOptions {
         }
     }
 
+    /// Uses the given cache for uncompressed data blocks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rocksdb::{BlockBasedOptions, Cache};
+    ///
+    /// let cache = Cache::new_lru_cache(64 << 20);
+    /// let mut opts = BlockBasedOptions::default();
+    /// opts.set_block_cache(&cache);
+    /// ```
+    pub fn set_block_cache(&mut self, cache: &Cache) {
+        unsafe {
+            ffi::rocksdb_block_based_options_set_block_cache(self.inner, cache.inner);
+        }
+    }
+
+    /// Uses the given cache for compressed data blocks.
+    pub fn set_block_cache_compressed(&mut self, cache: &Cache) {
+        unsafe {
+            ffi::rocksdb_block_based_options_set_block_cache_compressed(self.inner, cache.inner);
+        }
+    }
+
     pub fn disable_cache(&mut self) {
         unsafe {
             ffi::rocksdb_block_based_options_set_no_block_cache(self.inner, true as c_uchar);
@@ -584,6 +650,28 @@ impl Options {
         }
     }
 
+    /// Uses the given rate limiter to control the write rate of flushes and
+    /// compactions.
+    ///
+    /// The limiter is moved into the options, as RocksDB takes ownership of it.
+    /// Databases opened with these options share it.
+    ///
+    /// Default: none
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rocksdb::{Options, RateLimiter};
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_ratelimiter(RateLimiter::new(10 << 20, 100 * 1000, 10));
+    /// ```
+    pub fn set_ratelimiter(&mut self, limiter: RateLimiter) {
+        unsafe {
+            ffi::rocksdb_options_set_ratelimiter(self.inner, limiter.inner);
+        }
+    }
+
     /// If true, allow multi-writers to update mem tables in parallel.
     /// Only some memtable_factory-s support concurrent writes; currently it
     /// is implemented only for SkipListFactory.  Concurrent memtable writes
@@ -607,6 +695,24 @@ impl Options {
         }
     }
 
+    /// If true, the bottommost level is reserved for files added with
+    /// `IngestExternalFileOptions::set_ingest_behind`, so that compactions never
+    /// write to it.
+    ///
+    /// Default: false
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_allow_ingest_behind(true);
+    /// ```
+    pub fn set_allow_ingest_behind(&mut self, allow: bool) {
+        unsafe { ffi::rocksdb_options_set_allow_ingest_behind(self.inner, allow as c_uchar) }
+    }
+
     /// Enable direct I/O mode for reading
     /// they may or may not improve performance depending on the use case
     ///
@@ -1217,6 +1323,43 @@ impl Options {
         }
     }
 
+    /// Parses the current statistics once, for reading several of them, or
+    /// returns `None` if statistics are not enabled.
+    pub fn get_statistics_snapshot(&self) -> Option<StatisticsSnapshot> {
+        self.get_statistics()
+            .map(|stats| StatisticsSnapshot::parse(&stats))
+    }
+
+    /// Returns the current value of `ticker`, or `None` if statistics are not
+    /// enabled or the linked RocksDB does not report it.
+    ///
+    /// Each call dumps every statistic; use `get_statistics_snapshot` to read
+    /// several of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rocksdb::{Options, Ticker};
+    ///
+    /// let mut opts = Options::default();
+    /// opts.enable_statistics();
+    /// assert_eq!(opts.get_ticker_count(Ticker::BlockCacheMiss), Some(0));
+    /// ```
+    pub fn get_ticker_count(&self, ticker: Ticker) -> Option<u64> {
+        self.get_statistics_snapshot()
+            .and_then(|stats| stats.ticker_count(ticker))
+    }
+
+    /// Returns a summary of `histogram`, or `None` if statistics are not
+    /// enabled or the linked RocksDB does not report it.
+    ///
+    /// Each call dumps every statistic; use `get_statistics_snapshot` to read
+    /// several of them.
+    pub fn get_histogram_data(&self, histogram: Histogram) -> Option<HistogramData> {
+        self.get_statistics_snapshot()
+            .and_then(|stats| stats.histogram_data(histogram))
+    }
+
     /// If not zero, dump `rocksdb.stats` to LOG every `stats_dump_period_sec`.
     ///
     /// Default: `600` (10 mins)
@@ -1422,10 +1565,206 @@ impl Default for WriteOptions {
     }
 }
 
+impl IngestExternalFileOptions {
+    pub fn new() -> IngestExternalFileOptions {
+        IngestExternalFileOptions::default()
+    }
+
+    /// If set to true, the files are moved (hard linked) into the database
+    /// instead of being copied.
+    ///
+    /// Default: false
+    pub fn set_move_files(&mut self, move_files: bool) {
+        unsafe {
+            ffi::rocksdb_ingestexternalfileoptions_set_move_files(
+                self.inner,
+                move_files as c_uchar,
+            );
+        }
+    }
+
+    /// If set to true, snapshots taken before the ingestion will not see the
+    /// ingested keys.
+    ///
+    /// Default: true
+    pub fn set_snapshot_consistency(&mut self, snapshot_consistency: bool) {
+        unsafe {
+            ffi::rocksdb_ingestexternalfileoptions_set_snapshot_consistency(
+                self.inner,
+                snapshot_consistency as c_uchar,
+            );
+        }
+    }
+
+    /// If set to false, the ingestion fails when the key range of a file
+    /// overlaps with existing keys or tombstones, since the file would need a
+    /// global sequence number assigned to it.
+    ///
+    /// Default: true
+    pub fn set_allow_global_seqno(&mut self, allow_global_seqno: bool) {
+        unsafe {
+            ffi::rocksdb_ingestexternalfileoptions_set_allow_global_seqno(
+                self.inner,
+                allow_global_seqno as c_uchar,
+            );
+        }
+    }
+
+    /// If set to false and the key range of a file overlaps with the memtable,
+    /// the ingestion fails instead of flushing the memtable first.
+    ///
+    /// Default: true
+    pub fn set_allow_blocking_flush(&mut self, allow_blocking_flush: bool) {
+        unsafe {
+            ffi::rocksdb_ingestexternalfileoptions_set_allow_blocking_flush(
+                self.inner,
+                allow_blocking_flush as c_uchar,
+            );
+        }
+    }
+
+    /// If set to true, the files are ingested into the bottommost level with
+    /// a sequence number of zero, so that keys already in the database take
+    /// precedence over the ingested ones. Requires the database to be opened
+    /// with `Options::set_allow_ingest_behind`.
+    ///
+    /// Default: false
+    pub fn set_ingest_behind(&mut self, ingest_behind: bool) {
+        unsafe {
+            ffi::rocksdb_ingestexternalfileoptions_set_ingest_behind(
+                self.inner,
+                ingest_behind as c_uchar,
+            );
+        }
+    }
+}
+
+impl Cache {
+    /// Creates an LRU cache holding up to `capacity` bytes.
+    pub fn new_lru_cache(capacity: size_t) -> Cache {
+        let cache = new_cache(capacity);
+        if cache.is_null() {
+            panic!("Could not create RocksDB cache");
+        }
+        Cache { inner: cache }
+    }
+
+    /// Returns the memory size in bytes of the entries currently in the cache.
+    pub fn usage(&self) -> usize {
+        unsafe { ffi::rocksdb_cache_get_usage(self.inner) }
+    }
+
+    /// Returns the memory size in bytes of the entries that are pinned, for
+    /// example by iterators or index and filter blocks in use.
+    pub fn pinned_usage(&self) -> usize {
+        unsafe { ffi::rocksdb_cache_get_pinned_usage(self.inner) }
+    }
+
+    /// Sets the maximum size of the cache in bytes. If the new capacity is
+    /// smaller than the current usage, entries are evicted until it fits.
+    pub fn set_capacity(&self, capacity: size_t) {
+        unsafe {
+            ffi::rocksdb_cache_set_capacity(self.inner, capacity);
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter.
+    ///
+    /// `rate_bytes_per_sec` is the total write rate allowed. `refill_period_us`
+    /// controls how often tokens are refilled, which trades burstiness for
+    /// overhead; 100ms is a good default. `fairness` is the inverse of the
+    /// chance that low priority requests (compactions) are served before high
+    /// priority ones (flushes) when both are waiting; 10 is a good default.
+    pub fn new(rate_bytes_per_sec: i64, refill_period_us: i64, fairness: i32) -> RateLimiter {
+        let limiter = unsafe {
+            ffi::rocksdb_ratelimiter_create(rate_bytes_per_sec, refill_period_us, fairness)
+        };
+        if limiter.is_null() {
+            panic!("Could not create RocksDB rate limiter");
+        }
+        RateLimiter { inner: limiter }
+    }
+}
+
+impl Default for IngestExternalFileOptions {
+    fn default() -> IngestExternalFileOptions {
+        let ingest_opts = unsafe { ffi::rocksdb_ingestexternalfileoptions_create() };
+        if ingest_opts.is_null() {
+            panic!("Could not create RocksDB ingest external file options");
+        }
+        IngestExternalFileOptions { inner: ingest_opts }
+    }
+}
+
+impl CompactOptions {
+    pub fn new() -> CompactOptions {
+        CompactOptions::default()
+    }
+
+    /// If true, no other compaction will run while this manual compaction is running.
+    ///
+    /// Default: true
+    pub fn set_exclusive_manual_compaction(&mut self, exclusive: bool) {
+        unsafe {
+            ffi::rocksdb_compactoptions_set_exclusive_manual_compaction(
+                self.inner,
+                exclusive as c_uchar,
+            );
+        }
+    }
+
+    /// If true, the compacted files are moved to the level set with
+    /// `set_target_level` once the compaction is done.
+    ///
+    /// Default: false
+    pub fn set_change_level(&mut self, change_level: bool) {
+        unsafe {
+            ffi::rocksdb_compactoptions_set_change_level(self.inner, change_level as c_uchar);
+        }
+    }
+
+    /// The level the compacted files are moved to if `set_change_level` is
+    /// enabled. A negative value moves them to the lowest level that can hold them.
+    ///
+    /// Default: -1
+    pub fn set_target_level(&mut self, level: i32) {
+        unsafe {
+            ffi::rocksdb_compactoptions_set_target_level(self.inner, level as c_int);
+        }
+    }
+
+    /// Sets how the files of the bottommost level are compacted.
+    ///
+    /// Default: `BottommostLevelCompaction::IfHaveCompactionFilter`
+    pub fn set_bottommost_level_compaction(&mut self, mode: BottommostLevelCompaction) {
+        unsafe {
+            ffi::rocksdb_compactoptions_set_bottommost_level_compaction(
+                self.inner,
+                mode as c_uchar,
+            );
+        }
+    }
+}
+
+impl Default for CompactOptions {
+    fn default() -> CompactOptions {
+        let compact_opts = unsafe { ffi::rocksdb_compactoptions_create() };
+        if compact_opts.is_null() {
+            panic!("Could not create RocksDB compact options");
+        }
+        CompactOptions {
+            inner: compact_opts,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use MemtableFactory;
     use Options;
+    use {Histogram, Ticker};
 
     #[test]
     fn test_enable_statistics() {
@@ -1438,6 +1777,21 @@ mod tests {
         assert!(opts.get_statistics().is_none());
     }
 
+    #[test]
+    fn test_get_ticker_count() {
+        let mut opts = Options::default();
+        opts.enable_statistics();
+        assert_eq!(opts.get_ticker_count(Ticker::BytesWritten), Some(0));
+        assert_eq!(
+            opts.get_histogram_data(Histogram::DbGet).map(|h| h.count),
+            Some(0)
+        );
+
+        let opts = Options::default();
+        assert!(opts.get_ticker_count(Ticker::BytesWritten).is_none());
+        assert!(opts.get_histogram_data(Histogram::DbGet).is_none());
+    }
+
     #[test]
     fn test_set_memtable_factory() {
         let mut opts = Options::default();
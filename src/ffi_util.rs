@@ -13,9 +13,10 @@
 // limitations under the License.
 //
 
-use libc::{self, c_char, c_void};
+use libc::{self, c_char, c_void, size_t};
 use std::ffi::CStr;
 use std::ptr;
+use std::slice;
 
 pub fn error_message(ptr: *const c_char) -> String {
     let cstr = unsafe { CStr::from_ptr(ptr as *const _) };
@@ -26,6 +27,18 @@ pub fn error_message(ptr: *const c_char) -> String {
     s
 }
 
+/// Copies a buffer allocated by rocksdb into a `Vec` and frees the original.
+///
+/// Returns `None` if `ptr` is null, which is how the C API reports a missing key.
+pub unsafe fn raw_data(ptr: *mut c_char, len: size_t) -> Option<Vec<u8>> {
+    if ptr.is_null() {
+        return None;
+    }
+    let data = slice::from_raw_parts(ptr as *const u8, len as usize).to_vec();
+    libc::free(ptr as *mut c_void);
+    Some(data)
+}
+
 pub fn opt_bytes_to_ptr<T: AsRef<[u8]>>(opt: Option<T>) -> *const c_char {
     match opt {
         Some(v) => v.as_ref().as_ptr() as *const c_char,
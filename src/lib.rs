@@ -54,12 +54,22 @@
 //! ```
 //!
 
+#[cfg(feature = "typed")]
+extern crate bincode;
+#[cfg(feature = "async")]
+extern crate futures_core;
 extern crate libc;
 extern crate librocksdb_sys as ffi;
+#[cfg(feature = "typed")]
+extern crate serde;
+#[cfg(feature = "typed")]
+extern crate serde_json;
 
 #[macro_use]
 mod ffi_util;
 
+#[cfg(feature = "async")]
+mod async_db;
 pub mod backup;
 pub mod checkpoint;
 pub mod compaction_filter;
@@ -67,29 +77,66 @@ mod comparator;
 mod db;
 mod db_options;
 pub mod merge_operator;
+mod optimistic_transaction_db;
+mod read_only_db;
 mod slice_transform;
-
+mod sst_file_writer;
+mod statistics;
+mod transaction;
+mod transaction_db;
+#[cfg(feature = "typed")]
+pub mod typed;
+mod write_batch_ops;
+mod write_batch_with_index;
+
+#[cfg(feature = "async")]
+pub use async_db::{AsyncDB, AsyncDBIterator, BlockingPool, Next, Task};
 pub use compaction_filter::Decision as CompactionDecision;
 pub use db::{
     DBCompactionStyle, DBCompressionType, DBIterator, DBPinnableSlice, DBRawIterator,
-    DBRecoveryMode, DBWALIterator, Direction, IteratorMode, ReadOptions, Snapshot, WriteBatch,
+    DBRecoveryMode, DBWALIterator, Direction, IteratorMode, LiveFile, OwnedDBIterator,
+    OwnedDBRawIterator, OwnedSnapshot, Range, ReadOptions, Snapshot, WriteBatch,
     WriteBatchIterator,
 };
 
 pub use slice_transform::SliceTransform;
+pub use sst_file_writer::SstFileWriter;
+pub use statistics::{Histogram, HistogramData, StatisticsSnapshot, Ticker};
+pub use transaction::{Transaction, TransactionSnapshot};
+pub use transaction_db::{
+    TransactionDB, TransactionDBOptions, TransactionDBSnapshot, TransactionOptions,
+};
 
 pub use merge_operator::MergeOperands;
+pub use optimistic_transaction_db::{OptimisticTransactionDB, OptimisticTransactionOptions};
+pub use read_only_db::{ReadOnlyDB, SecondaryDB};
 use std::collections::BTreeMap;
 use std::error;
 use std::fmt;
+#[cfg(feature = "multi-threaded-cf")]
+use std::marker::PhantomData;
+#[cfg(feature = "multi-threaded-cf")]
+use std::ops::Deref;
 use std::path::PathBuf;
+#[cfg(feature = "multi-threaded-cf")]
+use std::sync::atomic::AtomicBool;
+#[cfg(feature = "multi-threaded-cf")]
+use std::sync::{Arc, Mutex, RwLock};
+pub use write_batch_ops::{WriteBatchOp, WriteBatchOps};
+pub use write_batch_with_index::WriteBatchWithIndex;
 
 /// A RocksDB database.
 ///
 /// See crate level documentation for a simple usage example.
 pub struct DB {
     inner: *mut ffi::rocksdb_t,
+    #[cfg(not(feature = "multi-threaded-cf"))]
     cfs: BTreeMap<String, ColumnFamily>,
+    #[cfg(feature = "multi-threaded-cf")]
+    cfs: RwLock<BTreeMap<String, Arc<SharedColumnFamily>>>,
+    // Handles of dropped column families that `cf_handle` lent out as plain references.
+    #[cfg(feature = "multi-threaded-cf")]
+    dropped_cfs: Mutex<Vec<Arc<SharedColumnFamily>>>,
     path: PathBuf,
 }
 
@@ -101,21 +148,131 @@ pub struct ColumnFamilyDescriptor {
     options: Options,
 }
 
-/// A simple wrapper round a string, used for errors reported from
-/// ffi calls.
+/// The class of failure reported by RocksDB, mirroring its `Status::Code`.
+///
+/// Errors raised by this crate itself rather than by RocksDB (for example an
+/// invalid path) are reported as `Unknown`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    NotFound,
+    Corruption,
+    NotSupported,
+    InvalidArgument,
+    IOError,
+    MergeInProgress,
+    Incomplete,
+    ShutdownInProgress,
+    TimedOut,
+    Aborted,
+    Busy,
+    Expired,
+    TryAgain,
+    CompactionTooLarge,
+    ColumnFamilyDropped,
+    Unknown,
+}
+
+/// Additional detail RocksDB attaches to some errors, mirroring its `Status::SubCode`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorSubCode {
+    MutexTimeout,
+    LockTimeout,
+    LockLimit,
+    NoSpace,
+    Deadlock,
+    StaleFile,
+    MemoryLimit,
+    SpaceLimit,
+    PathNotFound,
+}
+
+// The prefixes written by `Status::ToString`, in the order of `Status::Code`.
+const ERROR_KINDS: &[(&str, ErrorKind)] = &[
+    ("NotFound: ", ErrorKind::NotFound),
+    ("Corruption: ", ErrorKind::Corruption),
+    ("Not implemented: ", ErrorKind::NotSupported),
+    ("Invalid argument: ", ErrorKind::InvalidArgument),
+    ("IO error: ", ErrorKind::IOError),
+    ("Merge in progress: ", ErrorKind::MergeInProgress),
+    ("Result incomplete: ", ErrorKind::Incomplete),
+    ("Shutdown in progress: ", ErrorKind::ShutdownInProgress),
+    ("Operation timed out: ", ErrorKind::TimedOut),
+    ("Operation aborted: ", ErrorKind::Aborted),
+    ("Resource busy: ", ErrorKind::Busy),
+    ("Operation expired: ", ErrorKind::Expired),
+    ("Operation failed. Try again.: ", ErrorKind::TryAgain),
+    // `Status::ToString` has no text for this code and prints its number.
+    ("Unknown code(14): ", ErrorKind::CompactionTooLarge),
+    ("Column family dropped: ", ErrorKind::ColumnFamilyDropped),
+];
+
+// The sub-code messages `Status::ToString` writes straight after the prefix.
+const ERROR_SUB_CODES: &[(&str, ErrorSubCode)] = &[
+    ("Timeout Acquiring Mutex", ErrorSubCode::MutexTimeout),
+    ("Timeout waiting to lock key", ErrorSubCode::LockTimeout),
+    (
+        "Failed to acquire lock due to max_num_locks limit",
+        ErrorSubCode::LockLimit,
+    ),
+    ("No space left on device", ErrorSubCode::NoSpace),
+    ("Deadlock", ErrorSubCode::Deadlock),
+    ("Stale file handle", ErrorSubCode::StaleFile),
+    ("Memory limit reached", ErrorSubCode::MemoryLimit),
+    ("Space limit reached", ErrorSubCode::SpaceLimit),
+    ("No such file or directory", ErrorSubCode::PathNotFound),
+];
+
+/// An error reported from ffi calls, carrying the RocksDB status message
+/// along with the `ErrorKind` and `ErrorSubCode` parsed from it.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Error {
     message: String,
+    kind: ErrorKind,
+    sub_code: Option<ErrorSubCode>,
 }
 
 impl Error {
     fn new(message: String) -> Error {
-        Error { message }
+        let (kind, rest) = ERROR_KINDS
+            .iter()
+            .find(|&&(prefix, _)| message.starts_with(prefix))
+            .map(|&(prefix, kind)| (kind, &message[prefix.len()..]))
+            .unwrap_or((ErrorKind::Unknown, ""));
+        let sub_code = ERROR_SUB_CODES
+            .iter()
+            .find(|&&(msg, _)| rest.starts_with(msg))
+            .map(|&(_, sub_code)| sub_code);
+        Error {
+            message,
+            kind,
+            sub_code,
+        }
     }
 
     pub fn into_string(self) -> String {
         self.into()
     }
+
+    /// Returns the class of failure reported by RocksDB.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the additional detail RocksDB attached to the error, if any.
+    pub fn sub_code(&self) -> Option<ErrorSubCode> {
+        self.sub_code
+    }
+
+    /// Returns `true` if the operation failed because of a conflict or another
+    /// transient condition and may succeed if retried, as reported by
+    /// `Transaction::commit` on an `OptimisticTransactionDB` when a key the
+    /// transaction depends on was modified concurrently.
+    pub fn is_busy(&self) -> bool {
+        match self.kind {
+            ErrorKind::Busy | ErrorKind::TryAgain => true,
+            _ => false,
+        }
+    }
 }
 
 impl AsRef<str> for Error {
@@ -147,6 +304,32 @@ pub struct BlockBasedOptions {
     inner: *mut ffi::rocksdb_block_based_table_options_t,
 }
 
+/// An LRU block cache that can be shared between several `BlockBasedOptions`,
+/// so that all column families and databases using them draw from a single
+/// memory budget.
+///
+/// The cache stays alive as long as any table factory using it, so it may be
+/// dropped right after being attached.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{BlockBasedOptions, Cache, Options};
+///
+/// let cache = Cache::new_lru_cache(64 << 20);
+///
+/// let mut block_opts = BlockBasedOptions::default();
+/// block_opts.set_block_cache(&cache);
+///
+/// let mut opts = Options::default();
+/// opts.set_block_based_table_factory(&block_opts);
+///
+/// assert_eq!(cache.usage(), 0);
+/// ```
+pub struct Cache {
+    inner: *mut ffi::rocksdb_cache_t,
+}
+
 /// Used by BlockBasedOptions::set_index_type.
 pub enum BlockBasedIndexType {
     /// A space efficient index block that is optimized for
@@ -227,6 +410,39 @@ pub struct Options {
     inner: *mut ffi::rocksdb_options_t,
 }
 
+/// Limits the rate at which flushes and compactions write to disk.
+///
+/// A `RateLimiter` is consumed by `Options::set_ratelimiter`, since RocksDB
+/// takes ownership of it: attaching it to a second `Options` would silently
+/// leave that one unlimited. To share a budget, open every database with the
+/// same `Options`; the limiter stays alive as long as any of those databases.
+///
+/// # Examples
+///
+/// Sharing a 10MB/s limit between two databases:
+///
+/// ```
+/// use rocksdb::{DB, Options, RateLimiter};
+///
+/// let limiter = RateLimiter::new(10 << 20, 100 * 1000, 10);
+///
+/// let mut opts = Options::default();
+/// opts.create_if_missing(true);
+/// opts.set_ratelimiter(limiter);
+///
+/// let path1 = "_path_for_rocksdb_storage_ratelimiter1";
+/// let path2 = "_path_for_rocksdb_storage_ratelimiter2";
+/// {
+///     let db1 = DB::open(&opts, path1).unwrap();
+///     let db2 = DB::open(&opts, path2).unwrap();
+/// }
+/// let _ = DB::destroy(&Options::default(), path1);
+/// let _ = DB::destroy(&Options::default(), path2);
+/// ```
+pub struct RateLimiter {
+    inner: *mut ffi::rocksdb_ratelimiter_t,
+}
+
 /// Optionally wait for the memtable flush to be performed.
 ///
 /// # Examples
@@ -280,6 +496,75 @@ pub struct WriteOptions {
     inner: *mut ffi::rocksdb_writeoptions_t,
 }
 
+/// Options for adding SST files to a database with `DB::ingest_external_file`.
+///
+/// # Examples
+///
+/// Moving files written by an `SstFileWriter` into the database instead of
+/// copying them:
+///
+/// ```
+/// use rocksdb::{DB, IngestExternalFileOptions, Options, SstFileWriter};
+///
+/// let path = "_path_for_rocksdb_storage_ingest";
+/// let sst_path = "_path_for_rocksdb_storage_ingest.sst";
+/// {
+///     let opts = Options::default();
+///     let mut writer = SstFileWriter::new(&opts);
+///     writer.open(sst_path).unwrap();
+///     writer.put(b"key", b"value").unwrap();
+///     writer.finish().unwrap();
+///
+///     let db = DB::open_default(path).unwrap();
+///     let mut ingest_opts = IngestExternalFileOptions::default();
+///     ingest_opts.set_move_files(true);
+///     db.ingest_external_file_opt(&[sst_path], &ingest_opts).unwrap();
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// let _ = std::fs::remove_file(sst_path);
+/// ```
+pub struct IngestExternalFileOptions {
+    inner: *mut ffi::rocksdb_ingestexternalfileoptions_t,
+}
+
+/// Options for a manual compaction started with `DB::compact_range_opt`.
+///
+/// ```
+/// use rocksdb::{BottommostLevelCompaction, CompactOptions, Options, DB};
+///
+/// let path = "_path_for_compact_options";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     db.put(b"key", b"value").unwrap();
+///
+///     let mut compact_opts = CompactOptions::default();
+///     compact_opts.set_exclusive_manual_compaction(true);
+///     compact_opts.set_bottommost_level_compaction(BottommostLevelCompaction::Force);
+///     db.compact_range_opt(None::<&[u8]>, None::<&[u8]>, &compact_opts);
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct CompactOptions {
+    inner: *mut ffi::rocksdb_compactoptions_t,
+}
+
+/// Used by `CompactOptions::set_bottommost_level_compaction`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BottommostLevelCompaction {
+    /// Skip bottommost level compaction.
+    Skip = 0,
+
+    /// Only compact the bottommost level if there is a compaction filter.
+    IfHaveCompactionFilter,
+
+    /// Always compact the bottommost level.
+    Force,
+
+    /// Always compact the bottommost level, but avoid recompacting files
+    /// created by this compaction.
+    ForceOptimized,
+}
+
 /// An opaque type used to represent a column family. Returned from some functions, and used
 /// in others
 pub struct ColumnFamily {
@@ -287,11 +572,86 @@ pub struct ColumnFamily {
 }
 
 unsafe impl Send for ColumnFamily {}
+unsafe impl Sync for ColumnFamily {}
+
+/// A column family handle shared by a `DB` and the `BoundColumnFamily` handles
+/// to it, destroyed once none of them refer to it anymore.
+#[cfg(feature = "multi-threaded-cf")]
+pub(crate) struct SharedColumnFamily {
+    cf: ColumnFamily,
+    // Set once `DB::cf_handle` has lent out a plain reference to `cf`, which then
+    // has to stay valid until the database is closed.
+    lent: AtomicBool,
+}
+
+#[cfg(feature = "multi-threaded-cf")]
+impl Drop for SharedColumnFamily {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_column_family_handle_destroy(self.cf.inner);
+        }
+    }
+}
+
+/// A column family handle returned by `DB::bound_cf_handle` with the
+/// `multi-threaded-cf` feature.
+///
+/// It dereferences to `ColumnFamily`, and remains valid after its column family is
+/// dropped. The handle is released as soon as neither the `DB` nor any
+/// `BoundColumnFamily` refers to it. It borrows the `DB` it came from, so it can
+/// not outlive the database:
+///
+/// ```compile_fail
+/// use rocksdb::DB;
+///
+/// let cf = {
+///     let db = DB::open_default("_path_for_bound_column_family").unwrap();
+///     db.bound_cf_handle("cf1")
+/// };
+/// ```
+#[cfg(feature = "multi-threaded-cf")]
+#[derive(Clone)]
+pub struct BoundColumnFamily<'a> {
+    inner: Arc<SharedColumnFamily>,
+    db: PhantomData<&'a DB>,
+}
+
+#[cfg(feature = "multi-threaded-cf")]
+impl<'a> Deref for BoundColumnFamily<'a> {
+    type Target = ColumnFamily;
+
+    fn deref(&self) -> &ColumnFamily {
+        &self.inner.cf
+    }
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn error_kind() {
+        let e = Error::new("NotFound: ".to_owned());
+        assert_eq!(e.kind(), ErrorKind::NotFound);
+        assert_eq!(e.sub_code(), None);
+
+        let e = Error::new("IO error: No space left on deviceWhile appending to file".to_owned());
+        assert_eq!(e.kind(), ErrorKind::IOError);
+        assert_eq!(e.sub_code(), Some(ErrorSubCode::NoSpace));
+
+        let e = Error::new("Operation timed out: Timeout waiting to lock key".to_owned());
+        assert_eq!(e.kind(), ErrorKind::TimedOut);
+        assert_eq!(e.sub_code(), Some(ErrorSubCode::LockTimeout));
+        assert!(!e.is_busy());
+
+        let e = Error::new("Unknown code(14): ".to_owned());
+        assert_eq!(e.kind(), ErrorKind::CompactionTooLarge);
+
+        let e = Error::new("Failed to create RocksDB directory.".to_owned());
+        assert_eq!(e.kind(), ErrorKind::Unknown);
+        assert_eq!(e.sub_code(), None);
+    }
+
     #[test]
     fn is_send() {
         // test (at compile time) that certain types implement the auto-trait Send, either directly for
@@ -312,6 +672,32 @@ mod test {
         is_send::<PlainTableFactoryOptions>();
         is_send::<ColumnFamilyDescriptor>();
         is_send::<ColumnFamily>();
+        is_send::<TransactionDB>();
+        is_send::<Transaction<'_>>();
+        is_send::<TransactionDBOptions>();
+        is_send::<TransactionOptions>();
+        is_send::<TransactionDBSnapshot<'_>>();
+        is_send::<OptimisticTransactionDB>();
+        is_send::<OptimisticTransactionOptions>();
+        is_send::<SstFileWriter>();
+        is_send::<IngestExternalFileOptions>();
+        is_send::<RateLimiter>();
+        is_send::<Cache>();
+        is_send::<ReadOnlyDB>();
+        is_send::<SecondaryDB>();
+        is_send::<WriteBatch>();
+        is_send::<CompactOptions>();
+        is_send::<WriteBatchWithIndex>();
+        is_send::<OwnedSnapshot>();
+        is_send::<OwnedDBIterator>();
+        is_send::<OwnedDBRawIterator>();
+        #[cfg(feature = "multi-threaded-cf")]
+        is_send::<BoundColumnFamily<'_>>();
+        #[cfg(feature = "async")]
+        {
+            is_send::<AsyncDB>();
+            is_send::<AsyncDBIterator>();
+        }
     }
 
     #[test]
@@ -330,5 +716,26 @@ mod test {
         is_sync::<BlockBasedOptions>();
         is_sync::<PlainTableFactoryOptions>();
         is_sync::<ColumnFamilyDescriptor>();
+        is_sync::<ColumnFamily>();
+        is_sync::<TransactionDB>();
+        is_sync::<TransactionDBOptions>();
+        is_sync::<TransactionOptions>();
+        is_sync::<TransactionDBSnapshot<'_>>();
+        is_sync::<OptimisticTransactionDB>();
+        is_sync::<OptimisticTransactionOptions>();
+        is_sync::<SstFileWriter>();
+        is_sync::<IngestExternalFileOptions>();
+        is_sync::<RateLimiter>();
+        is_sync::<Cache>();
+        is_sync::<ReadOnlyDB>();
+        is_sync::<SecondaryDB>();
+        is_sync::<WriteBatch>();
+        is_sync::<CompactOptions>();
+        is_sync::<WriteBatchWithIndex>();
+        is_sync::<OwnedSnapshot>();
+        #[cfg(feature = "multi-threaded-cf")]
+        is_sync::<BoundColumnFamily<'_>>();
+        #[cfg(feature = "async")]
+        is_sync::<AsyncDB>();
     }
 }
@@ -0,0 +1,291 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use db::open_cf_handles;
+use ffi;
+use {ColumnFamilyDescriptor, Error, Options, Transaction, WriteOptions, DB};
+
+use libc::c_uchar;
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::fmt;
+use std::fs;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::path::Path;
+use std::ptr;
+
+/// A RocksDB database with support for optimistic transactions.
+///
+/// Optimistic transactions do not take locks while they run. Instead, the keys
+/// a transaction wrote (or read with `get_for_update`) are validated when it
+/// commits, and the commit fails with an error for which `Error::is_busy`
+/// returns `true` if any of them were modified by someone else in the meantime.
+/// This avoids locking overhead for workloads where conflicts are rare.
+///
+/// An `OptimisticTransactionDB` dereferences to the underlying `DB`, so
+/// non-transactional reads, writes and iterators use the regular `DB` API.
+/// This is safe because optimistic transactions hold no locks that such writes
+/// would need to respect; `TransactionDB` cannot do the same.
+///
+/// ```
+/// use rocksdb::{OptimisticTransactionDB, Options, DB};
+///
+/// let path = "_path_for_optimistic_transaction_db";
+/// {
+///     let db = OptimisticTransactionDB::open_default(path).unwrap();
+///     db.put(b"my key", b"old value").unwrap();
+///
+///     let txn = db.transaction();
+///     txn.get_for_update(b"my key", true).unwrap();
+///     txn.put(b"my key", b"new value").unwrap();
+///
+///     // Someone else writes the key before the transaction commits.
+///     db.put(b"my key", b"other value").unwrap();
+///
+///     match txn.commit() {
+///         Err(e) if e.is_busy() => println!("conflict, retrying"),
+///         other => other.unwrap(),
+///     }
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct OptimisticTransactionDB {
+    inner: *mut ffi::rocksdb_optimistictransactiondb_t,
+    // Owned by `inner`, so it must never be dropped as a regular `DB`.
+    db: ManuallyDrop<DB>,
+}
+
+unsafe impl Send for OptimisticTransactionDB {}
+unsafe impl Sync for OptimisticTransactionDB {}
+
+/// Options used when beginning a `Transaction` on an `OptimisticTransactionDB`.
+pub struct OptimisticTransactionOptions {
+    inner: *mut ffi::rocksdb_optimistictransaction_options_t,
+}
+
+// Safety note: auto-implementing Send on most db-related types is prevented by the inner FFI
+// pointer. In most cases, however, this pointer is Send-safe because it is never aliased and
+// rocksdb internally does not rely on thread-local information for its user-exposed types.
+unsafe impl Send for OptimisticTransactionOptions {}
+
+// Sync is similarly safe for many types because they do not expose interior mutability, and their
+// use within the rocksdb library is generally behind a const reference
+unsafe impl Sync for OptimisticTransactionOptions {}
+
+impl OptimisticTransactionDB {
+    /// Open an optimistic transaction database with default options.
+    pub fn open_default<P: AsRef<Path>>(path: P) -> Result<OptimisticTransactionDB, Error> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        OptimisticTransactionDB::open(&opts, path)
+    }
+
+    /// Open an optimistic transaction database with the specified options.
+    pub fn open<P: AsRef<Path>>(opts: &Options, path: P) -> Result<OptimisticTransactionDB, Error> {
+        OptimisticTransactionDB::open_cf(opts, path, None::<&str>)
+    }
+
+    /// Open an optimistic transaction database with the given database options and column
+    /// family names.
+    ///
+    /// Column families opened using this function will be created with default `Options`.
+    pub fn open_cf<P, I, N>(
+        opts: &Options,
+        path: P,
+        cfs: I,
+    ) -> Result<OptimisticTransactionDB, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = N>,
+        N: AsRef<str>,
+    {
+        let cfs = cfs
+            .into_iter()
+            .map(|name| ColumnFamilyDescriptor::new(name.as_ref(), Options::default()));
+
+        OptimisticTransactionDB::open_cf_descriptors(opts, path, cfs)
+    }
+
+    /// Open an optimistic transaction database with the given database options and column
+    /// family descriptors.
+    pub fn open_cf_descriptors<P, I>(
+        opts: &Options,
+        path: P,
+        cfs: I,
+    ) -> Result<OptimisticTransactionDB, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = ColumnFamilyDescriptor>,
+    {
+        let cfs: Vec<_> = cfs.into_iter().collect();
+
+        let path = path.as_ref();
+        let cpath = match CString::new(path.to_string_lossy().as_bytes()) {
+            Ok(c) => c,
+            Err(_) => {
+                return Err(Error::new(
+                    "Failed to convert path to CString \
+                     when opening DB."
+                        .to_owned(),
+                ));
+            }
+        };
+
+        if let Err(e) = fs::create_dir_all(&path) {
+            return Err(Error::new(format!(
+                "Failed to create RocksDB directory: `{:?}`.",
+                e
+            )));
+        }
+
+        let (db, cf_map) = if cfs.is_empty() {
+            let db = unsafe {
+                ffi_try!(ffi::rocksdb_optimistictransactiondb_open(
+                    opts.inner,
+                    cpath.as_ptr() as *const _,
+                ))
+            };
+            (db, BTreeMap::new())
+        } else {
+            open_cf_handles(
+                cfs,
+                |num_cfs, cfnames, cfopts, cfhandles| unsafe {
+                    Ok(ffi_try!(
+                        ffi::rocksdb_optimistictransactiondb_open_column_families(
+                            opts.inner,
+                            cpath.as_ptr(),
+                            num_cfs,
+                            cfnames,
+                            cfopts,
+                            cfhandles,
+                        )
+                    ))
+                },
+                ffi::rocksdb_optimistictransactiondb_close,
+            )?
+        };
+
+        if db.is_null() {
+            return Err(Error::new("Could not initialize database.".to_owned()));
+        }
+
+        let base_db = unsafe { ffi::rocksdb_optimistictransactiondb_get_base_db(db) };
+        if base_db.is_null() {
+            unsafe {
+                for cf in cf_map.values() {
+                    ffi::rocksdb_column_family_handle_destroy(cf.inner);
+                }
+                ffi::rocksdb_optimistictransactiondb_close(db);
+            }
+            return Err(Error::new("Could not initialize database.".to_owned()));
+        }
+
+        Ok(OptimisticTransactionDB {
+            inner: db,
+            db: ManuallyDrop::new(DB::from_parts(base_db, cf_map, path.to_path_buf())),
+        })
+    }
+
+    /// Begins a new optimistic transaction with default options.
+    pub fn transaction(&self) -> Transaction {
+        self.transaction_opt(
+            &WriteOptions::default(),
+            &OptimisticTransactionOptions::default(),
+        )
+    }
+
+    /// Begins a new optimistic transaction with the given write and transaction options.
+    pub fn transaction_opt(
+        &self,
+        writeopts: &WriteOptions,
+        txn_opts: &OptimisticTransactionOptions,
+    ) -> Transaction {
+        unsafe {
+            Transaction::from_inner(ffi::rocksdb_optimistictransaction_begin(
+                self.inner,
+                writeopts.inner,
+                txn_opts.inner,
+                ptr::null_mut(),
+            ))
+        }
+    }
+}
+
+impl Deref for OptimisticTransactionDB {
+    type Target = DB;
+
+    fn deref(&self) -> &DB {
+        &self.db
+    }
+}
+
+impl Drop for OptimisticTransactionDB {
+    fn drop(&mut self) {
+        unsafe {
+            self.db.destroy_cf_handles();
+            ffi::rocksdb_optimistictransactiondb_close_base_db(self.db.inner);
+            ffi::rocksdb_optimistictransactiondb_close(self.inner);
+        }
+    }
+}
+
+impl fmt::Debug for OptimisticTransactionDB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "RocksDB OptimisticTransactionDB {{ path: {:?} }}",
+            self.path()
+        )
+    }
+}
+
+impl OptimisticTransactionOptions {
+    pub fn new() -> OptimisticTransactionOptions {
+        OptimisticTransactionOptions::default()
+    }
+
+    /// If true, conflicts are checked against a snapshot taken when the
+    /// transaction begins rather than when each key is first written or read
+    /// with `get_for_update`.
+    ///
+    /// Default: `false`
+    pub fn set_snapshot(&mut self, snapshot: bool) {
+        unsafe {
+            ffi::rocksdb_optimistictransaction_options_set_set_snapshot(
+                self.inner,
+                snapshot as c_uchar,
+            );
+        }
+    }
+}
+
+impl Default for OptimisticTransactionOptions {
+    fn default() -> OptimisticTransactionOptions {
+        let txn_opts = unsafe { ffi::rocksdb_optimistictransaction_options_create() };
+        if txn_opts.is_null() {
+            panic!("Could not create RocksDB optimistic transaction options");
+        }
+        OptimisticTransactionOptions { inner: txn_opts }
+    }
+}
+
+impl Drop for OptimisticTransactionOptions {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_optimistictransaction_options_destroy(self.inner);
+        }
+    }
+}
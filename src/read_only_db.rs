@@ -0,0 +1,476 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use db::AccessType;
+use ffi;
+use {
+    ColumnFamily, ColumnFamilyDescriptor, DBIterator, DBPinnableSlice, DBRawIterator, Direction,
+    Error, IteratorMode, Options, ReadOptions, Snapshot, DB,
+};
+
+use std::fmt;
+use std::ops::{Deref, RangeBounds};
+use std::path::Path;
+
+/// A database opened in read-only mode.
+///
+/// A read-only instance does not take the database lock, so it can be opened
+/// while another process has the database open for writing. It sees the data
+/// as of the time it was opened and only offers read operations.
+///
+/// ```
+/// use rocksdb::{DB, Options, ReadOnlyDB};
+///
+/// let path = "_path_for_read_only_db";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     db.put(b"my key", b"my value").unwrap();
+///
+///     let read_only = ReadOnlyDB::open(&Options::default(), path, false).unwrap();
+///     assert_eq!(read_only.get(b"my key").unwrap().unwrap(), b"my value");
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct ReadOnlyDB {
+    db: DB,
+}
+
+/// A database opened as a secondary instance of a primary that is being
+/// written to by another process.
+///
+/// Like a `ReadOnlyDB`, which it dereferences to, a secondary instance only
+/// offers read operations. Unlike it, it can follow the primary's writes by
+/// calling `try_catch_up_with_primary`. The secondary keeps its own info logs
+/// in a separate directory given when it is opened.
+///
+/// ```
+/// use rocksdb::{DB, Options, SecondaryDB};
+///
+/// let path = "_path_for_secondary_db";
+/// let secondary_path = "_path_for_secondary_db_secondary";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     let mut opts = Options::default();
+///     opts.set_max_open_files(-1);
+///     let secondary = SecondaryDB::open(&opts, path, secondary_path).unwrap();
+///
+///     db.put(b"my key", b"my value").unwrap();
+///     secondary.try_catch_up_with_primary().unwrap();
+///     assert_eq!(secondary.get(b"my key").unwrap().unwrap(), b"my value");
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// let _ = std::fs::remove_dir_all(secondary_path);
+/// ```
+pub struct SecondaryDB {
+    db: ReadOnlyDB,
+}
+
+impl ReadOnlyDB {
+    /// Open a database in read-only mode with the specified options.
+    ///
+    /// If `error_if_log_file_exist` is true, opening fails when the database
+    /// has write-ahead log files that were not flushed, i.e. when the primary
+    /// may hold data this instance would not see.
+    pub fn open<P: AsRef<Path>>(
+        opts: &Options,
+        path: P,
+        error_if_log_file_exist: bool,
+    ) -> Result<ReadOnlyDB, Error> {
+        ReadOnlyDB::open_cf(opts, path, None::<&str>, error_if_log_file_exist)
+    }
+
+    /// Open a database in read-only mode with the given database options and column family
+    /// names.
+    ///
+    /// Column families opened using this function will be created with default `Options`.
+    pub fn open_cf<P, I, N>(
+        opts: &Options,
+        path: P,
+        cfs: I,
+        error_if_log_file_exist: bool,
+    ) -> Result<ReadOnlyDB, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = N>,
+        N: AsRef<str>,
+    {
+        let cfs = cfs
+            .into_iter()
+            .map(|name| ColumnFamilyDescriptor::new(name.as_ref(), Options::default()));
+
+        ReadOnlyDB::open_cf_descriptors(opts, path, cfs, error_if_log_file_exist)
+    }
+
+    /// Open a database in read-only mode with the given database options and column family
+    /// descriptors.
+    pub fn open_cf_descriptors<P, I>(
+        opts: &Options,
+        path: P,
+        cfs: I,
+        error_if_log_file_exist: bool,
+    ) -> Result<ReadOnlyDB, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = ColumnFamilyDescriptor>,
+    {
+        let access_type = AccessType::ReadOnly {
+            error_if_log_file_exist,
+        };
+        DB::open_cf_descriptors_internal(opts, path, cfs, &access_type).map(|db| ReadOnlyDB { db })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.db.path()
+    }
+
+    pub fn cf_handle(&self, name: &str) -> Option<&ColumnFamily> {
+        self.db.cf_handle(name)
+    }
+
+    /// Return the bytes associated with a key value with read options.
+    pub fn get_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.db.get_opt(key, readopts)
+    }
+
+    /// Return the bytes associated with a key value.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Vec<u8>>, Error> {
+        self.db.get(key)
+    }
+
+    /// Return the bytes associated with a key value and the given column family with read
+    /// options.
+    pub fn get_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.db.get_cf_opt(cf, key, readopts)
+    }
+
+    /// Return the bytes associated with a key value and the given column family.
+    pub fn get_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.db.get_cf(cf, key)
+    }
+
+    /// Return the value associated with a key using RocksDB's PinnableSlice
+    /// so as to avoid unnecessary memory copy.
+    pub fn get_pinned_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<DBPinnableSlice>, Error> {
+        self.db.get_pinned_opt(key, readopts)
+    }
+
+    /// Return the value associated with a key using RocksDB's PinnableSlice
+    /// so as to avoid unnecessary memory copy.
+    pub fn get_pinned<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<DBPinnableSlice>, Error> {
+        self.db.get_pinned(key)
+    }
+
+    /// Return the value associated with a key and the given column family using RocksDB's
+    /// PinnableSlice so as to avoid unnecessary memory copy.
+    pub fn get_pinned_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<DBPinnableSlice>, Error> {
+        self.db.get_pinned_cf_opt(cf, key, readopts)
+    }
+
+    /// Return the value associated with a key and the given column family using RocksDB's
+    /// PinnableSlice so as to avoid unnecessary memory copy.
+    pub fn get_pinned_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<DBPinnableSlice>, Error> {
+        self.db.get_pinned_cf(cf, key)
+    }
+
+    /// Return the values associated with the given keys, in the same order as the keys.
+    pub fn multi_get<K, I>(&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        self.db.multi_get(keys)
+    }
+
+    /// Return the values associated with the given keys using read options.
+    pub fn multi_get_opt<K, I>(
+        &self,
+        keys: I,
+        readopts: &ReadOptions,
+    ) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        self.db.multi_get_opt(keys, readopts)
+    }
+
+    /// Return the values associated with the given keys and column families, in the same order
+    /// as the keys.
+    pub fn multi_get_cf<'c, K, I>(&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        self.db.multi_get_cf(keys)
+    }
+
+    /// Return the values associated with the given keys and column families using read options.
+    pub fn multi_get_cf_opt<'c, K, I>(
+        &self,
+        keys: I,
+        readopts: &ReadOptions,
+    ) -> Vec<Result<Option<Vec<u8>>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        self.db.multi_get_cf_opt(keys, readopts)
+    }
+
+    pub fn iterator(&self, mode: IteratorMode) -> DBIterator {
+        self.db.iterator(mode)
+    }
+
+    pub fn iterator_opt(&self, mode: IteratorMode, readopts: ReadOptions) -> DBIterator {
+        self.db.iterator_opt(mode, readopts)
+    }
+
+    pub fn iterator_cf(
+        &self,
+        cf_handle: &ColumnFamily,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        self.db.iterator_cf(cf_handle, mode)
+    }
+
+    /// Opens an iterator using the provided ReadOptions.
+    /// This is used when you want to iterate over a specific ColumnFamily with a modified ReadOptions
+    pub fn iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        self.db.iterator_cf_opt(cf_handle, readopts, mode)
+    }
+
+    /// Opens an iterator with `set_total_order_seek` enabled.
+    /// This must be used to iterate across prefixes when `set_memtable_factory` has been called
+    /// with a Hash-based implementation.
+    pub fn full_iterator(&self, mode: IteratorMode) -> DBIterator {
+        self.db.full_iterator(mode)
+    }
+
+    pub fn full_iterator_cf(
+        &self,
+        cf_handle: &ColumnFamily,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        self.db.full_iterator_cf(cf_handle, mode)
+    }
+
+    pub fn prefix_iterator<P: AsRef<[u8]>>(&self, prefix: P) -> DBIterator {
+        self.db.prefix_iterator(prefix)
+    }
+
+    pub fn prefix_iterator_cf<P: AsRef<[u8]>>(
+        &self,
+        cf_handle: &ColumnFamily,
+        prefix: P,
+    ) -> Result<DBIterator, Error> {
+        self.db.prefix_iterator_cf(cf_handle, prefix)
+    }
+
+    /// See `DB::range_iterator`.
+    pub fn range_iterator<K, R>(&self, range: R, direction: Direction) -> DBIterator
+    where
+        K: AsRef<[u8]>,
+        R: RangeBounds<K>,
+    {
+        self.db.range_iterator(range, direction)
+    }
+
+    pub fn range_iterator_cf<K, R>(
+        &self,
+        cf_handle: &ColumnFamily,
+        range: R,
+        direction: Direction,
+    ) -> Result<DBIterator, Error>
+    where
+        K: AsRef<[u8]>,
+        R: RangeBounds<K>,
+    {
+        self.db.range_iterator_cf(cf_handle, range, direction)
+    }
+
+    /// Opens a raw iterator over the database, using the default read options
+    pub fn raw_iterator(&self) -> DBRawIterator {
+        self.db.raw_iterator()
+    }
+
+    /// Opens a raw iterator over the given column family, using the default read options
+    pub fn raw_iterator_cf(&self, cf_handle: &ColumnFamily) -> Result<DBRawIterator, Error> {
+        self.db.raw_iterator_cf(cf_handle)
+    }
+
+    /// Opens a raw iterator over the database, using the given read options
+    pub fn raw_iterator_opt(&self, readopts: ReadOptions) -> DBRawIterator {
+        self.db.raw_iterator_opt(readopts)
+    }
+
+    /// Opens a raw iterator over the given column family, using the given read options
+    pub fn raw_iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+    ) -> Result<DBRawIterator, Error> {
+        self.db.raw_iterator_cf_opt(cf_handle, readopts)
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.db.snapshot()
+    }
+
+    /// Retrieves a RocksDB property by name.
+    pub fn property_value(&self, name: &str) -> Result<Option<String>, Error> {
+        self.db.property_value(name)
+    }
+
+    /// Retrieves a RocksDB property by name, for a specific column family.
+    pub fn property_value_cf(
+        &self,
+        cf: &ColumnFamily,
+        name: &str,
+    ) -> Result<Option<String>, Error> {
+        self.db.property_value_cf(cf, name)
+    }
+
+    /// Retrieves a RocksDB property and casts it to an integer.
+    pub fn property_int_value(&self, name: &str) -> Result<Option<u64>, Error> {
+        self.db.property_int_value(name)
+    }
+
+    /// Retrieves a RocksDB property for a specific column family and casts it to an integer.
+    pub fn property_int_value_cf(
+        &self,
+        cf: &ColumnFamily,
+        name: &str,
+    ) -> Result<Option<u64>, Error> {
+        self.db.property_int_value_cf(cf, name)
+    }
+}
+
+impl fmt::Debug for ReadOnlyDB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RocksDB ReadOnlyDB {{ path: {:?} }}", self.path())
+    }
+}
+
+impl SecondaryDB {
+    /// Open a secondary instance of the database at `path` with the specified options,
+    /// keeping the instance's own info logs in `secondary_path`.
+    ///
+    /// `Options::set_max_open_files(-1)` is recommended for secondary instances,
+    /// since the primary may delete table files the secondary has not opened yet.
+    pub fn open<P, S>(opts: &Options, path: P, secondary_path: S) -> Result<SecondaryDB, Error>
+    where
+        P: AsRef<Path>,
+        S: AsRef<Path>,
+    {
+        SecondaryDB::open_cf(opts, path, secondary_path, None::<&str>)
+    }
+
+    /// Open a secondary instance with the given database options and column family names.
+    ///
+    /// Column families opened using this function will be created with default `Options`.
+    pub fn open_cf<P, S, I, N>(
+        opts: &Options,
+        path: P,
+        secondary_path: S,
+        cfs: I,
+    ) -> Result<SecondaryDB, Error>
+    where
+        P: AsRef<Path>,
+        S: AsRef<Path>,
+        I: IntoIterator<Item = N>,
+        N: AsRef<str>,
+    {
+        let cfs = cfs
+            .into_iter()
+            .map(|name| ColumnFamilyDescriptor::new(name.as_ref(), Options::default()));
+
+        SecondaryDB::open_cf_descriptors(opts, path, secondary_path, cfs)
+    }
+
+    /// Open a secondary instance with the given database options and column family
+    /// descriptors.
+    pub fn open_cf_descriptors<P, S, I>(
+        opts: &Options,
+        path: P,
+        secondary_path: S,
+        cfs: I,
+    ) -> Result<SecondaryDB, Error>
+    where
+        P: AsRef<Path>,
+        S: AsRef<Path>,
+        I: IntoIterator<Item = ColumnFamilyDescriptor>,
+    {
+        let access_type = AccessType::Secondary {
+            secondary_path: secondary_path.as_ref(),
+        };
+        DB::open_cf_descriptors_internal(opts, path, cfs, &access_type).map(|db| SecondaryDB {
+            db: ReadOnlyDB { db },
+        })
+    }
+
+    /// Makes the writes the primary has made since the instance was opened, or
+    /// since the last call, visible to this instance.
+    pub fn try_catch_up_with_primary(&self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_try_catch_up_with_primary(self.db.db.inner,));
+        }
+        Ok(())
+    }
+}
+
+impl Deref for SecondaryDB {
+    type Target = ReadOnlyDB;
+
+    fn deref(&self) -> &ReadOnlyDB {
+        &self.db
+    }
+}
+
+impl fmt::Debug for SecondaryDB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RocksDB SecondaryDB {{ path: {:?} }}", self.path())
+    }
+}
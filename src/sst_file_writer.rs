@@ -0,0 +1,227 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use ffi;
+use {Error, Options};
+
+use libc::{c_char, size_t};
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Writes sorted key-value pairs into an SST file that can later be added to a
+/// database with `DB::ingest_external_file`.
+///
+/// The writer uses the comparator, merge operator and compression configured on
+/// the `Options` it is created from, which should therefore match the options of
+/// the column family the file will be ingested into. Keys must be added in
+/// strictly increasing order according to that comparator.
+///
+/// ```
+/// use rocksdb::{DB, Options, SstFileWriter};
+///
+/// let path = "_path_for_sst_file_writer";
+/// let sst_path = "_path_for_sst_file_writer.sst";
+/// {
+///     let opts = Options::default();
+///     let mut writer = SstFileWriter::new(&opts);
+///     writer.open(sst_path).unwrap();
+///     writer.put(b"k1", b"v1").unwrap();
+///     writer.put(b"k2", b"v2").unwrap();
+///     writer.finish().unwrap();
+///
+///     let db = DB::open_default(path).unwrap();
+///     db.ingest_external_file(&[sst_path]).unwrap();
+///     assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v2");
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// let _ = std::fs::remove_file(sst_path);
+/// ```
+pub struct SstFileWriter<'a> {
+    inner: *mut ffi::rocksdb_sstfilewriter_t,
+    smallest_key: Option<Vec<u8>>,
+    largest_key: Option<Vec<u8>>,
+    num_entries: u64,
+    // The writer keeps pointers to the comparator and merge operator owned by `Options`.
+    options: PhantomData<&'a Options>,
+}
+
+// Safety note: auto-implementing Send on most db-related types is prevented by the inner FFI
+// pointer. In most cases, however, this pointer is Send-safe because it is never aliased and
+// rocksdb internally does not rely on thread-local information for its user-exposed types.
+unsafe impl<'a> Send for SstFileWriter<'a> {}
+
+// Sync is similarly safe for many types because they do not expose interior mutability, and their
+// use within the rocksdb library is generally behind a const reference
+unsafe impl<'a> Sync for SstFileWriter<'a> {}
+
+impl<'a> SstFileWriter<'a> {
+    /// Creates a writer that builds SST files compatible with the given options.
+    pub fn new(opts: &'a Options) -> SstFileWriter<'a> {
+        let writer = unsafe {
+            let env_opts = ffi::rocksdb_envoptions_create();
+            let writer = ffi::rocksdb_sstfilewriter_create(env_opts, opts.inner);
+            // The writer keeps its own copy of the env options.
+            ffi::rocksdb_envoptions_destroy(env_opts);
+            writer
+        };
+        if writer.is_null() {
+            panic!("Could not create RocksDB sst file writer");
+        }
+        SstFileWriter {
+            inner: writer,
+            smallest_key: None,
+            largest_key: None,
+            num_entries: 0,
+            options: PhantomData,
+        }
+    }
+
+    /// Prepares the writer to write a new SST file at `path`, replacing any
+    /// file that already exists there.
+    pub fn open<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let cpath = match CString::new(path.to_string_lossy().as_bytes()) {
+            Ok(c) => c,
+            Err(_) => {
+                return Err(Error::new(
+                    "Failed to convert path to CString when opening SST file writer.".to_owned(),
+                ));
+            }
+        };
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_sstfilewriter_open(self.inner, cpath.as_ptr(),));
+        }
+        self.smallest_key = None;
+        self.largest_key = None;
+        self.num_entries = 0;
+        Ok(())
+    }
+
+    /// Adds a put of `key` to the file.
+    pub fn put<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_sstfilewriter_put(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+        }
+        self.record(key);
+        Ok(())
+    }
+
+    /// Adds a merge of `value` into `key` to the file.
+    pub fn merge<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_sstfilewriter_merge(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+        }
+        self.record(key);
+        Ok(())
+    }
+
+    /// Adds a deletion of `key` to the file.
+    pub fn delete<K: AsRef<[u8]>>(&mut self, key: K) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_sstfilewriter_delete(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            ));
+        }
+        self.record(key);
+        Ok(())
+    }
+
+    /// Finalizes the file currently being written. The file cannot be written
+    /// to afterwards, but the writer can be reused by calling `open` again.
+    pub fn finish(&mut self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_sstfilewriter_finish(self.inner,));
+        }
+        Ok(())
+    }
+
+    /// Returns the size in bytes of the file currently being written.
+    pub fn file_size(&self) -> u64 {
+        let mut file_size: u64 = 0;
+        unsafe {
+            ffi::rocksdb_sstfilewriter_file_size(self.inner, &mut file_size);
+        }
+        file_size
+    }
+
+    /// Returns the first key written to the current file, if any.
+    pub fn smallest_key(&self) -> Option<&[u8]> {
+        self.smallest_key.as_ref().map(|k| k.as_slice())
+    }
+
+    /// Returns the last key written to the current file, if any.
+    pub fn largest_key(&self) -> Option<&[u8]> {
+        self.largest_key.as_ref().map(|k| k.as_slice())
+    }
+
+    /// Returns the number of puts, merges and deletions written to the current file.
+    pub fn num_entries(&self) -> u64 {
+        self.num_entries
+    }
+
+    fn record(&mut self, key: &[u8]) {
+        if self.smallest_key.is_none() {
+            self.smallest_key = Some(key.to_vec());
+        }
+        match self.largest_key {
+            Some(ref mut largest) => {
+                largest.clear();
+                largest.extend_from_slice(key);
+            }
+            None => self.largest_key = Some(key.to_vec()),
+        }
+        self.num_entries += 1;
+    }
+}
+
+impl<'a> Drop for SstFileWriter<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_sstfilewriter_destroy(self.inner);
+        }
+    }
+}
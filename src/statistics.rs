@@ -0,0 +1,359 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::HashMap;
+
+macro_rules! named_statistics {
+    ($(#[$attr:meta])* pub enum $name:ident { $($variant:ident => $str:expr,)* }) => {
+        $(#[$attr])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)*
+        }
+
+        impl $name {
+            /// Returns the name RocksDB reports this statistic under.
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    $($name::$variant => $str,)*
+                }
+            }
+        }
+    };
+}
+
+named_statistics! {
+    /// A counter collected by RocksDB when statistics are enabled with
+    /// `Options::enable_statistics`.
+    pub enum Ticker {
+        BlockCacheMiss => "rocksdb.block.cache.miss",
+        BlockCacheHit => "rocksdb.block.cache.hit",
+        BlockCacheAdd => "rocksdb.block.cache.add",
+        BlockCacheAddFailures => "rocksdb.block.cache.add.failures",
+        BlockCacheIndexMiss => "rocksdb.block.cache.index.miss",
+        BlockCacheIndexHit => "rocksdb.block.cache.index.hit",
+        BlockCacheIndexAdd => "rocksdb.block.cache.index.add",
+        BlockCacheIndexBytesInsert => "rocksdb.block.cache.index.bytes.insert",
+        BlockCacheIndexBytesEvict => "rocksdb.block.cache.index.bytes.evict",
+        BlockCacheFilterMiss => "rocksdb.block.cache.filter.miss",
+        BlockCacheFilterHit => "rocksdb.block.cache.filter.hit",
+        BlockCacheFilterAdd => "rocksdb.block.cache.filter.add",
+        BlockCacheFilterBytesInsert => "rocksdb.block.cache.filter.bytes.insert",
+        BlockCacheFilterBytesEvict => "rocksdb.block.cache.filter.bytes.evict",
+        BlockCacheDataMiss => "rocksdb.block.cache.data.miss",
+        BlockCacheDataHit => "rocksdb.block.cache.data.hit",
+        BlockCacheDataAdd => "rocksdb.block.cache.data.add",
+        BlockCacheDataBytesInsert => "rocksdb.block.cache.data.bytes.insert",
+        BlockCacheBytesRead => "rocksdb.block.cache.bytes.read",
+        BlockCacheBytesWrite => "rocksdb.block.cache.bytes.write",
+        BloomFilterUseful => "rocksdb.bloom.filter.useful",
+        BloomFilterFullPositive => "rocksdb.bloom.filter.full.positive",
+        BloomFilterFullTruePositive => "rocksdb.bloom.filter.full.true.positive",
+        BloomFilterMicros => "rocksdb.bloom.filter.micros",
+        PersistentCacheHit => "rocksdb.persistent.cache.hit",
+        PersistentCacheMiss => "rocksdb.persistent.cache.miss",
+        SimBlockCacheHit => "rocksdb.sim.block.cache.hit",
+        SimBlockCacheMiss => "rocksdb.sim.block.cache.miss",
+        MemtableHit => "rocksdb.memtable.hit",
+        MemtableMiss => "rocksdb.memtable.miss",
+        GetHitL0 => "rocksdb.l0.hit",
+        GetHitL1 => "rocksdb.l1.hit",
+        GetHitL2AndUp => "rocksdb.l2andup.hit",
+        CompactionKeyDropNewerEntry => "rocksdb.compaction.key.drop.new",
+        CompactionKeyDropObsolete => "rocksdb.compaction.key.drop.obsolete",
+        CompactionKeyDropRangeDel => "rocksdb.compaction.key.drop.range_del",
+        CompactionKeyDropUser => "rocksdb.compaction.key.drop.user",
+        CompactionRangeDelDropObsolete => "rocksdb.compaction.range_del.drop.obsolete",
+        CompactionOptimizedDelDropObsolete => "rocksdb.compaction.optimized.del.drop.obsolete",
+        CompactionCancelled => "rocksdb.compaction.cancelled",
+        NumberKeysWritten => "rocksdb.number.keys.written",
+        NumberKeysRead => "rocksdb.number.keys.read",
+        NumberKeysUpdated => "rocksdb.number.keys.updated",
+        BytesWritten => "rocksdb.bytes.written",
+        BytesRead => "rocksdb.bytes.read",
+        NumberDbSeek => "rocksdb.number.db.seek",
+        NumberDbNext => "rocksdb.number.db.next",
+        NumberDbPrev => "rocksdb.number.db.prev",
+        NumberDbSeekFound => "rocksdb.number.db.seek.found",
+        NumberDbNextFound => "rocksdb.number.db.next.found",
+        NumberDbPrevFound => "rocksdb.number.db.prev.found",
+        IterBytesRead => "rocksdb.db.iter.bytes.read",
+        NoFileCloses => "rocksdb.no.file.closes",
+        NoFileOpens => "rocksdb.no.file.opens",
+        NoFileErrors => "rocksdb.no.file.errors",
+        StallL0SlowdownMicros => "rocksdb.l0.slowdown.micros",
+        StallMemtableCompactionMicros => "rocksdb.memtable.compaction.micros",
+        StallL0NumFilesMicros => "rocksdb.l0.num.files.stall.micros",
+        StallMicros => "rocksdb.stall.micros",
+        DbMutexWaitMicros => "rocksdb.db.mutex.wait.micros",
+        RateLimitDelayMillis => "rocksdb.rate.limit.delay.millis",
+        NoIterators => "rocksdb.num.iterators",
+        NumberMultigetCalls => "rocksdb.number.multiget.get",
+        NumberMultigetKeysRead => "rocksdb.number.multiget.keys.read",
+        NumberMultigetBytesRead => "rocksdb.number.multiget.bytes.read",
+        NumberFilteredDeletes => "rocksdb.number.deletes.filtered",
+        NumberMergeFailures => "rocksdb.number.merge.failures",
+        BloomFilterPrefixChecked => "rocksdb.bloom.filter.prefix.checked",
+        BloomFilterPrefixUseful => "rocksdb.bloom.filter.prefix.useful",
+        NumberOfReseeksInIteration => "rocksdb.number.reseeks.iteration",
+        GetUpdatesSinceCalls => "rocksdb.getupdatessince.calls",
+        BlockCacheCompressedMiss => "rocksdb.block.cachecompressed.miss",
+        BlockCacheCompressedHit => "rocksdb.block.cachecompressed.hit",
+        BlockCacheCompressedAdd => "rocksdb.block.cachecompressed.add",
+        BlockCacheCompressedAddFailures => "rocksdb.block.cachecompressed.add.failures",
+        WalFileSynced => "rocksdb.wal.synced",
+        WalFileBytes => "rocksdb.wal.bytes",
+        WriteDoneBySelf => "rocksdb.write.self",
+        WriteDoneByOther => "rocksdb.write.other",
+        WriteTimedout => "rocksdb.write.timeout",
+        WriteWithWal => "rocksdb.write.wal",
+        CompactReadBytes => "rocksdb.compact.read.bytes",
+        CompactWriteBytes => "rocksdb.compact.write.bytes",
+        FlushWriteBytes => "rocksdb.flush.write.bytes",
+        NumberDirectLoadTableProperties => "rocksdb.number.direct.load.table.properties",
+        NumberSuperversionAcquires => "rocksdb.number.superversion_acquires",
+        NumberSuperversionReleases => "rocksdb.number.superversion_releases",
+        NumberSuperversionCleanups => "rocksdb.number.superversion_cleanups",
+        NumberBlockCompressed => "rocksdb.number.block.compressed",
+        NumberBlockDecompressed => "rocksdb.number.block.decompressed",
+        NumberBlockNotCompressed => "rocksdb.number.block.not_compressed",
+        MergeOperationTotalTime => "rocksdb.merge.operation.time.nanos",
+        FilterOperationTotalTime => "rocksdb.filter.operation.time.nanos",
+        RowCacheHit => "rocksdb.row.cache.hit",
+        RowCacheMiss => "rocksdb.row.cache.miss",
+        ReadAmpEstimateUsefulBytes => "rocksdb.read.amp.estimate.useful.bytes",
+        ReadAmpTotalReadBytes => "rocksdb.read.amp.total.read.bytes",
+        NumberRateLimiterDrains => "rocksdb.number.rate_limiter.drains",
+        NumberIterSkip => "rocksdb.number.iter.skip",
+        BlobDbNumPut => "rocksdb.blobdb.num.put",
+        BlobDbNumWrite => "rocksdb.blobdb.num.write",
+        BlobDbNumGet => "rocksdb.blobdb.num.get",
+        BlobDbNumMultiget => "rocksdb.blobdb.num.multiget",
+        BlobDbNumSeek => "rocksdb.blobdb.num.seek",
+        BlobDbNumNext => "rocksdb.blobdb.num.next",
+        BlobDbNumPrev => "rocksdb.blobdb.num.prev",
+        BlobDbNumKeysWritten => "rocksdb.blobdb.num.keys.written",
+        BlobDbNumKeysRead => "rocksdb.blobdb.num.keys.read",
+        BlobDbBytesWritten => "rocksdb.blobdb.bytes.written",
+        BlobDbBytesRead => "rocksdb.blobdb.bytes.read",
+        BlobDbWriteInlined => "rocksdb.blobdb.write.inlined",
+        BlobDbWriteInlinedTtl => "rocksdb.blobdb.write.inlined.ttl",
+        BlobDbWriteBlob => "rocksdb.blobdb.write.blob",
+        BlobDbWriteBlobTtl => "rocksdb.blobdb.write.blob.ttl",
+        BlobDbBlobFileBytesWritten => "rocksdb.blobdb.blob.file.bytes.written",
+        BlobDbBlobFileBytesRead => "rocksdb.blobdb.blob.file.bytes.read",
+        BlobDbBlobFileSynced => "rocksdb.blobdb.blob.file.synced",
+        BlobDbBlobIndexExpiredCount => "rocksdb.blobdb.blob.index.expired.count",
+        BlobDbBlobIndexExpiredSize => "rocksdb.blobdb.blob.index.expired.size",
+        BlobDbBlobIndexEvictedCount => "rocksdb.blobdb.blob.index.evicted.count",
+        BlobDbBlobIndexEvictedSize => "rocksdb.blobdb.blob.index.evicted.size",
+        BlobDbGcNumFiles => "rocksdb.blobdb.gc.num.files",
+        BlobDbGcNumNewFiles => "rocksdb.blobdb.gc.num.new.files",
+        BlobDbGcFailures => "rocksdb.blobdb.gc.failures",
+        BlobDbGcNumKeysOverwritten => "rocksdb.blobdb.gc.num.keys.overwritten",
+        BlobDbGcNumKeysExpired => "rocksdb.blobdb.gc.num.keys.expired",
+        BlobDbGcNumKeysRelocated => "rocksdb.blobdb.gc.num.keys.relocated",
+        BlobDbGcBytesOverwritten => "rocksdb.blobdb.gc.bytes.overwritten",
+        BlobDbGcBytesExpired => "rocksdb.blobdb.gc.bytes.expired",
+        BlobDbGcBytesRelocated => "rocksdb.blobdb.gc.bytes.relocated",
+        BlobDbFifoNumFilesEvicted => "rocksdb.blobdb.fifo.num.files.evicted",
+        BlobDbFifoNumKeysEvicted => "rocksdb.blobdb.fifo.num.keys.evicted",
+        BlobDbFifoBytesEvicted => "rocksdb.blobdb.fifo.bytes.evicted",
+        TxnPrepareMutexOverhead => "rocksdb.txn.overhead.mutex.prepare",
+        TxnOldCommitMapMutexOverhead => "rocksdb.txn.overhead.mutex.old.commit.map",
+        TxnDuplicateKeyOverhead => "rocksdb.txn.overhead.duplicate.key",
+        TxnSnapshotMutexOverhead => "rocksdb.txn.overhead.mutex.snapshot",
+        TxnGetTryAgain => "rocksdb.txn.get.tryagain",
+        NumberMultigetKeysFound => "rocksdb.number.multiget.keys.found",
+        NoIteratorCreated => "rocksdb.num.iterator.created",
+        NoIteratorDeleted => "rocksdb.num.iterator.deleted",
+        BlockCacheCompressionDictMiss => "rocksdb.block.cache.compression.dict.miss",
+        BlockCacheCompressionDictHit => "rocksdb.block.cache.compression.dict.hit",
+        BlockCacheCompressionDictAdd => "rocksdb.block.cache.compression.dict.add",
+        BlockCacheCompressionDictBytesInsert => "rocksdb.block.cache.compression.dict.bytes.insert",
+        BlockCacheCompressionDictBytesEvict => "rocksdb.block.cache.compression.dict.bytes.evict",
+    }
+}
+
+named_statistics! {
+    /// A distribution collected by RocksDB when statistics are enabled with
+    /// `Options::enable_statistics`.
+    pub enum Histogram {
+        DbGet => "rocksdb.db.get.micros",
+        DbWrite => "rocksdb.db.write.micros",
+        CompactionTime => "rocksdb.compaction.times.micros",
+        CompactionCpuTime => "rocksdb.compaction.times.cpu_micros",
+        SubcompactionSetupTime => "rocksdb.subcompaction.setup.times.micros",
+        TableSyncMicros => "rocksdb.table.sync.micros",
+        CompactionOutfileSyncMicros => "rocksdb.compaction.outfile.sync.micros",
+        WalFileSyncMicros => "rocksdb.wal.file.sync.micros",
+        ManifestFileSyncMicros => "rocksdb.manifest.file.sync.micros",
+        TableOpenIoMicros => "rocksdb.table.open.io.micros",
+        DbMultiget => "rocksdb.db.multiget.micros",
+        ReadBlockCompactionMicros => "rocksdb.read.block.compaction.micros",
+        ReadBlockGetMicros => "rocksdb.read.block.get.micros",
+        WriteRawBlockMicros => "rocksdb.write.raw.block.micros",
+        StallL0SlowdownCount => "rocksdb.l0.slowdown.count",
+        StallMemtableCompactionCount => "rocksdb.memtable.compaction.count",
+        StallL0NumFilesCount => "rocksdb.num.files.stall.count",
+        HardRateLimitDelayCount => "rocksdb.hard.rate.limit.delay.count",
+        SoftRateLimitDelayCount => "rocksdb.soft.rate.limit.delay.count",
+        NumFilesInSingleCompaction => "rocksdb.numfiles.in.singlecompaction",
+        DbSeek => "rocksdb.db.seek.micros",
+        WriteStall => "rocksdb.db.write.stall",
+        SstReadMicros => "rocksdb.sst.read.micros",
+        NumSubcompactionsScheduled => "rocksdb.num.subcompactions.scheduled",
+        BytesPerRead => "rocksdb.bytes.per.read",
+        BytesPerWrite => "rocksdb.bytes.per.write",
+        BytesPerMultiget => "rocksdb.bytes.per.multiget",
+        BytesCompressed => "rocksdb.bytes.compressed",
+        BytesDecompressed => "rocksdb.bytes.decompressed",
+        CompressionTimesNanos => "rocksdb.compression.times.nanos",
+        DecompressionTimesNanos => "rocksdb.decompression.times.nanos",
+        ReadNumMergeOperands => "rocksdb.read.num.merge_operands",
+        BlobDbKeySize => "rocksdb.blobdb.key.size",
+        BlobDbValueSize => "rocksdb.blobdb.value.size",
+        BlobDbWriteMicros => "rocksdb.blobdb.write.micros",
+        BlobDbGetMicros => "rocksdb.blobdb.get.micros",
+        BlobDbMultigetMicros => "rocksdb.blobdb.multiget.micros",
+        BlobDbSeekMicros => "rocksdb.blobdb.seek.micros",
+        BlobDbNextMicros => "rocksdb.blobdb.next.micros",
+        BlobDbPrevMicros => "rocksdb.blobdb.prev.micros",
+        BlobDbBlobFileWriteMicros => "rocksdb.blobdb.blob.file.write.micros",
+        BlobDbBlobFileReadMicros => "rocksdb.blobdb.blob.file.read.micros",
+        BlobDbBlobFileSyncMicros => "rocksdb.blobdb.blob.file.sync.micros",
+        BlobDbGcMicros => "rocksdb.blobdb.gc.micros",
+        BlobDbCompressionMicros => "rocksdb.blobdb.compression.micros",
+        BlobDbDecompressionMicros => "rocksdb.blobdb.decompression.micros",
+        FlushTime => "rocksdb.db.flush.micros",
+        SstBatchSize => "rocksdb.sst.batch.size",
+    }
+}
+
+/// A summary of the values recorded in a `Histogram`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HistogramData {
+    pub median: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+    pub count: u64,
+    pub sum: u64,
+}
+
+/// The tickers and histograms of a single statistics dump, parsed once.
+///
+/// Reading several statistics from a snapshot is much cheaper than calling
+/// `Options::get_ticker_count` or `Options::get_histogram_data` for each of
+/// them, as those dump and parse every statistic on each call.
+///
+/// # Example
+///
+/// ```
+/// use rocksdb::{Histogram, Options, Ticker};
+///
+/// let mut opts = Options::default();
+/// opts.enable_statistics();
+/// let stats = opts.get_statistics_snapshot().unwrap();
+/// assert_eq!(stats.ticker_count(Ticker::BlockCacheMiss), Some(0));
+/// assert_eq!(stats.histogram_data(Histogram::DbGet).unwrap().count, 0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StatisticsSnapshot {
+    tickers: HashMap<String, u64>,
+    histograms: HashMap<String, HistogramData>,
+}
+
+impl StatisticsSnapshot {
+    /// Parses the output of `Options::get_statistics`.
+    pub(crate) fn parse(stats: &str) -> StatisticsSnapshot {
+        let mut snapshot = StatisticsSnapshot::default();
+        for line in stats.lines() {
+            let mut tokens = line.split_whitespace();
+            let name = match tokens.next() {
+                Some(name) => name,
+                None => continue,
+            };
+            let fields: Vec<&str> = tokens.collect();
+            if let Some(data) = parse_histogram_data(&fields) {
+                snapshot.histograms.insert(name.to_owned(), data);
+            } else if let Some(count) = field(&fields, "COUNT").and_then(|c| c.parse().ok()) {
+                snapshot.tickers.insert(name.to_owned(), count);
+            }
+        }
+        snapshot
+    }
+
+    /// Returns the value of `ticker`, or `None` if the linked RocksDB does not
+    /// report it.
+    pub fn ticker_count(&self, ticker: Ticker) -> Option<u64> {
+        self.tickers.get(ticker.name()).cloned()
+    }
+
+    /// Returns a summary of `histogram`, or `None` if the linked RocksDB does
+    /// not report it.
+    pub fn histogram_data(&self, histogram: Histogram) -> Option<HistogramData> {
+        self.histograms.get(histogram.name()).cloned()
+    }
+}
+
+fn parse_histogram_data(fields: &[&str]) -> Option<HistogramData> {
+    Some(HistogramData {
+        median: field(fields, "P50")?.parse().ok()?,
+        p95: field(fields, "P95")?.parse().ok()?,
+        p99: field(fields, "P99")?.parse().ok()?,
+        max: field(fields, "P100")?.parse().ok()?,
+        count: field(fields, "COUNT")?.parse().ok()?,
+        sum: field(fields, "SUM")?.parse().ok()?,
+    })
+}
+
+// Returns the value following `label :` in a line of `label : value` pairs.
+fn field<'a>(fields: &[&'a str], label: &str) -> Option<&'a str> {
+    fields
+        .windows(3)
+        .find(|t| t[0] == label && t[1] == ":")
+        .map(|t| t[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATS: &str = "rocksdb.block.cache.add COUNT : 7\n\
+                         rocksdb.block.cache.add.failures COUNT : 2\n\
+                         rocksdb.db.get.micros P50 : 1.500000 P95 : 3.000000 P99 : 4.250000 \
+                         P100 : 9.000000 COUNT : 12 SUM : 30\n";
+
+    #[test]
+    fn parse_statistics() {
+        let stats = StatisticsSnapshot::parse(STATS);
+        assert_eq!(stats.ticker_count(Ticker::BlockCacheAdd), Some(7));
+        assert_eq!(stats.ticker_count(Ticker::BlockCacheAddFailures), Some(2));
+        assert_eq!(stats.ticker_count(Ticker::BlockCacheMiss), None);
+        assert_eq!(
+            stats.histogram_data(Histogram::DbGet),
+            Some(HistogramData {
+                median: 1.5,
+                p95: 3.0,
+                p99: 4.25,
+                max: 9.0,
+                count: 12,
+                sum: 30,
+            })
+        );
+        assert_eq!(stats.histogram_data(Histogram::DbWrite), None);
+    }
+}
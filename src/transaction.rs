@@ -0,0 +1,588 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use ffi;
+use ffi_util::raw_data;
+use {ColumnFamily, DBIterator, DBRawIterator, Error, IteratorMode, ReadOptions};
+
+use libc::{c_char, c_uchar, c_void, size_t};
+use std::marker::PhantomData;
+
+/// A transaction against a `TransactionDB` or an `OptimisticTransactionDB`.
+///
+/// Writes made through a transaction are buffered until `commit` is called and
+/// are visible to reads made through the same transaction. Dropping a
+/// transaction without committing it discards its writes.
+///
+/// ```
+/// use rocksdb::{Options, TransactionDB};
+///
+/// let path = "_path_for_transaction_db";
+/// {
+///     let db = TransactionDB::open_default(path).unwrap();
+///     let txn = db.transaction();
+///     txn.put(b"my key", b"my value").unwrap();
+///     assert!(db.get(b"my key").unwrap().is_none());
+///     txn.commit().unwrap();
+///     assert_eq!(db.get(b"my key").unwrap().unwrap(), b"my value");
+/// }
+/// let _ = rocksdb::DB::destroy(&Options::default(), path);
+/// ```
+pub struct Transaction<'a> {
+    inner: *mut ffi::rocksdb_transaction_t,
+    db: PhantomData<&'a ()>,
+}
+
+// Safety note: a transaction may be moved to another thread, but rocksdb does not
+// allow a single transaction to be used from several threads at once, so it is not `Sync`.
+unsafe impl<'a> Send for Transaction<'a> {}
+
+/// The snapshot a `Transaction` reads from, obtained with `Transaction::snapshot`.
+///
+/// Reads made through it see the database as of the snapshot, merged with the
+/// writes of the transaction, so reading a key twice returns the same value
+/// even if it was committed by someone else in the meantime.
+///
+/// ```
+/// use rocksdb::{Options, TransactionDB, TransactionDBOptions, TransactionOptions, WriteOptions};
+///
+/// let path = "_path_for_transaction_snapshot";
+/// {
+///     let db = TransactionDB::open_default(path).unwrap();
+///     db.put(b"my key", b"old value").unwrap();
+///
+///     let mut txn_opts = TransactionOptions::default();
+///     txn_opts.set_snapshot(true);
+///     let txn = db.transaction_opt(&WriteOptions::default(), &txn_opts);
+///     db.put(b"my key", b"new value").unwrap();
+///
+///     let snapshot = txn.snapshot();
+///     assert_eq!(snapshot.get(b"my key").unwrap().unwrap(), b"old value");
+///     assert_eq!(txn.get(b"my key").unwrap().unwrap(), b"new value");
+/// }
+/// let _ = rocksdb::DB::destroy(&Options::default(), path);
+/// ```
+pub struct TransactionSnapshot<'a> {
+    txn: &'a Transaction<'a>,
+    inner: *const ffi::rocksdb_snapshot_t,
+}
+
+impl<'a> Transaction<'a> {
+    /// Wraps a transaction returned by one of the rocksdb `begin` functions.
+    ///
+    /// # Unsafe
+    /// Requires that the database the transaction was started on outlives `'a`.
+    pub(crate) unsafe fn from_inner(inner: *mut ffi::rocksdb_transaction_t) -> Transaction<'a> {
+        Transaction {
+            inner,
+            db: PhantomData,
+        }
+    }
+
+    /// Writes all batched keys to the database atomically.
+    pub fn commit(self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_transaction_commit(self.inner,));
+        }
+        Ok(())
+    }
+
+    /// Discards all batched writes and releases any locks held by this transaction.
+    pub fn rollback(&self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_transaction_rollback(self.inner,));
+        }
+        Ok(())
+    }
+
+    /// Records the state of the transaction so that later writes can be undone
+    /// with `rollback_to_savepoint`. Savepoints may be nested.
+    pub fn set_savepoint(&self) {
+        unsafe {
+            ffi::rocksdb_transaction_set_savepoint(self.inner);
+        }
+    }
+
+    /// Undoes all writes made since the most recent call to `set_savepoint` and
+    /// removes that savepoint.
+    ///
+    /// Returns an error if no savepoint has been set.
+    pub fn rollback_to_savepoint(&self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_transaction_rollback_to_savepoint(self.inner,));
+        }
+        Ok(())
+    }
+
+    /// Returns the snapshot this transaction reads from.
+    ///
+    /// A transaction has a snapshot if it was begun with
+    /// `TransactionOptions::set_snapshot` or
+    /// `OptimisticTransactionOptions::set_snapshot`. Otherwise, reads made
+    /// through the returned value see the latest committed data.
+    pub fn snapshot(&self) -> TransactionSnapshot {
+        let inner = unsafe { ffi::rocksdb_transaction_get_snapshot(self.inner) };
+        TransactionSnapshot { txn: self, inner }
+    }
+
+    /// Returns the value associated with a key, including writes made by this
+    /// transaction that have not been committed yet.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Vec<u8>>, Error> {
+        self.get_opt(key, &ReadOptions::default())
+    }
+
+    /// Returns the value associated with a key using the given read options.
+    pub fn get_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+        let mut val_len: size_t = 0;
+
+        unsafe {
+            let val = ffi_try!(ffi::rocksdb_transaction_get(
+                self.inner,
+                readopts.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    /// Returns the value associated with a key in the given column family.
+    pub fn get_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_cf_opt(cf, key, &ReadOptions::default())
+    }
+
+    /// Returns the value associated with a key in the given column family using
+    /// the given read options.
+    pub fn get_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+        let mut val_len: size_t = 0;
+
+        unsafe {
+            let val = ffi_try!(ffi::rocksdb_transaction_get_cf(
+                self.inner,
+                readopts.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    /// Reads a key and locks it so that no other transaction can write it until
+    /// this transaction is committed or rolled back.
+    ///
+    /// If `exclusive` is false the lock is shared with other readers that also
+    /// called `get_for_update` in non-exclusive mode.
+    pub fn get_for_update<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        exclusive: bool,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_for_update_opt(key, exclusive, &ReadOptions::default())
+    }
+
+    /// Reads and locks a key using the given read options. See `get_for_update`.
+    pub fn get_for_update_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        exclusive: bool,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+        let mut val_len: size_t = 0;
+
+        unsafe {
+            let val = ffi_try!(ffi::rocksdb_transaction_get_for_update(
+                self.inner,
+                readopts.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+                exclusive as c_uchar,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    /// Reads and locks a key in the given column family. See `get_for_update`.
+    pub fn get_for_update_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        exclusive: bool,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_for_update_cf_opt(cf, key, exclusive, &ReadOptions::default())
+    }
+
+    /// Reads and locks a key in the given column family using the given read
+    /// options. See `get_for_update`.
+    pub fn get_for_update_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        exclusive: bool,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+        let mut val_len: size_t = 0;
+
+        unsafe {
+            let val = ffi_try!(ffi::rocksdb_transaction_get_for_update_cf(
+                self.inner,
+                readopts.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+                exclusive as c_uchar,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    pub fn put<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transaction_put(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn put_cf<K, V>(&self, cf: &ColumnFamily, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transaction_put_cf(
+                self.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn merge<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transaction_merge(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn merge_cf<K, V>(&self, cf: &ColumnFamily, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transaction_merge_cf(
+                self.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transaction_delete(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn delete_cf<K: AsRef<[u8]>>(&self, cf: &ColumnFamily, key: K) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transaction_delete_cf(
+                self.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    /// Opens an iterator over the database merged with the writes of this transaction.
+    pub fn iterator(&self, mode: IteratorMode) -> DBIterator {
+        self.iterator_opt(mode, ReadOptions::default())
+    }
+
+    pub fn iterator_opt(&self, mode: IteratorMode, readopts: ReadOptions) -> DBIterator {
+        DBIterator::from_raw(self.raw_iterator_opt(readopts), mode)
+    }
+
+    pub fn iterator_cf(
+        &self,
+        cf_handle: &ColumnFamily,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        self.iterator_cf_opt(cf_handle, ReadOptions::default(), mode)
+    }
+
+    pub fn iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        Ok(DBIterator::from_raw(
+            self.raw_iterator_cf_opt(cf_handle, readopts)?,
+            mode,
+        ))
+    }
+
+    /// Opens a raw iterator over the database merged with the writes of this
+    /// transaction, using the default read options.
+    pub fn raw_iterator(&self) -> DBRawIterator {
+        self.raw_iterator_opt(ReadOptions::default())
+    }
+
+    pub fn raw_iterator_opt(&self, readopts: ReadOptions) -> DBRawIterator {
+        unsafe {
+            let inner = ffi::rocksdb_transaction_create_iterator(self.inner, readopts.inner);
+            DBRawIterator::from_inner(inner, readopts)
+        }
+    }
+
+    pub fn raw_iterator_cf(&self, cf_handle: &ColumnFamily) -> Result<DBRawIterator, Error> {
+        self.raw_iterator_cf_opt(cf_handle, ReadOptions::default())
+    }
+
+    pub fn raw_iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+    ) -> Result<DBRawIterator, Error> {
+        unsafe {
+            let inner = ffi::rocksdb_transaction_create_iterator_cf(
+                self.inner,
+                readopts.inner,
+                cf_handle.inner,
+            );
+            Ok(DBRawIterator::from_inner(inner, readopts))
+        }
+    }
+}
+
+impl<'a> TransactionSnapshot<'a> {
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Vec<u8>>, Error> {
+        self.get_opt(key, ReadOptions::default())
+    }
+
+    pub fn get_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.set_snapshot(&mut readopts);
+        self.txn.get_opt(key, &readopts)
+    }
+
+    pub fn get_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_cf_opt(cf, key, ReadOptions::default())
+    }
+
+    pub fn get_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.set_snapshot(&mut readopts);
+        self.txn.get_cf_opt(cf, key, &readopts)
+    }
+
+    /// Reads and locks a key, failing if it was written after the snapshot was
+    /// taken. See `Transaction::get_for_update`.
+    pub fn get_for_update<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        exclusive: bool,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_for_update_opt(key, exclusive, ReadOptions::default())
+    }
+
+    pub fn get_for_update_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        exclusive: bool,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.set_snapshot(&mut readopts);
+        self.txn.get_for_update_opt(key, exclusive, &readopts)
+    }
+
+    pub fn get_for_update_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        exclusive: bool,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_for_update_cf_opt(cf, key, exclusive, ReadOptions::default())
+    }
+
+    pub fn get_for_update_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        exclusive: bool,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.set_snapshot(&mut readopts);
+        self.txn
+            .get_for_update_cf_opt(cf, key, exclusive, &readopts)
+    }
+
+    pub fn iterator(&self, mode: IteratorMode) -> DBIterator {
+        self.iterator_opt(mode, ReadOptions::default())
+    }
+
+    pub fn iterator_opt(&self, mode: IteratorMode, readopts: ReadOptions) -> DBIterator {
+        DBIterator::from_raw(self.raw_iterator_opt(readopts), mode)
+    }
+
+    pub fn iterator_cf(
+        &self,
+        cf_handle: &ColumnFamily,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        self.iterator_cf_opt(cf_handle, ReadOptions::default(), mode)
+    }
+
+    pub fn iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        Ok(DBIterator::from_raw(
+            self.raw_iterator_cf_opt(cf_handle, readopts)?,
+            mode,
+        ))
+    }
+
+    pub fn raw_iterator(&self) -> DBRawIterator {
+        self.raw_iterator_opt(ReadOptions::default())
+    }
+
+    pub fn raw_iterator_opt(&self, mut readopts: ReadOptions) -> DBRawIterator {
+        self.set_snapshot(&mut readopts);
+        self.txn.raw_iterator_opt(readopts)
+    }
+
+    pub fn raw_iterator_cf(&self, cf_handle: &ColumnFamily) -> Result<DBRawIterator, Error> {
+        self.raw_iterator_cf_opt(cf_handle, ReadOptions::default())
+    }
+
+    pub fn raw_iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        mut readopts: ReadOptions,
+    ) -> Result<DBRawIterator, Error> {
+        self.set_snapshot(&mut readopts);
+        self.txn.raw_iterator_cf_opt(cf_handle, readopts)
+    }
+
+    fn set_snapshot(&self, readopts: &mut ReadOptions) {
+        unsafe {
+            ffi::rocksdb_readoptions_set_snapshot(readopts.inner, self.inner);
+        }
+    }
+}
+
+impl<'a> Drop for TransactionSnapshot<'a> {
+    fn drop(&mut self) {
+        // Only the handle is freed here; the snapshot itself belongs to the transaction.
+        unsafe {
+            ffi::rocksdb_free(self.inner as *mut c_void);
+        }
+    }
+}
+
+impl<'a> Drop for Transaction<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_transaction_destroy(self.inner);
+        }
+    }
+}
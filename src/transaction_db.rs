@@ -0,0 +1,884 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use db::open_cf_handles;
+use ffi;
+use ffi_util::raw_data;
+use {
+    ColumnFamily, ColumnFamilyDescriptor, DBIterator, DBRawIterator, Error, IteratorMode, Options,
+    ReadOptions, Transaction, WriteBatch, WriteOptions,
+};
+
+use libc::{c_char, c_uchar, size_t};
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+/// A RocksDB database with support for pessimistic transactions.
+///
+/// Keys written or read with `get_for_update` inside a `Transaction` are locked
+/// until the transaction is committed or rolled back, so conflicting
+/// transactions fail (or wait for the lock timeout) instead of overwriting each
+/// other's writes.
+///
+/// Unlike an `OptimisticTransactionDB`, a `TransactionDB` does not dereference
+/// to a `DB`: the C API gives no access to its base database, and writes made
+/// outside of a transaction must still go through the `TransactionDB` so that
+/// they wait for the locks held by transactions. It therefore provides the
+/// non-transactional reads, writes and iterators of `DB` itself.
+///
+/// ```
+/// use rocksdb::{Options, TransactionDB, TransactionDBOptions};
+///
+/// let path = "_path_for_transaction_db_storage";
+/// {
+///     let mut opts = Options::default();
+///     opts.create_if_missing(true);
+///     let txn_db_opts = TransactionDBOptions::default();
+///     let db = TransactionDB::open(&opts, &txn_db_opts, path).unwrap();
+///
+///     let txn = db.transaction();
+///     let counter = txn.get_for_update(b"counter", true).unwrap();
+///     assert!(counter.is_none());
+///     txn.put(b"counter", b"1").unwrap();
+///     txn.commit().unwrap();
+/// }
+/// let _ = rocksdb::DB::destroy(&Options::default(), path);
+/// ```
+pub struct TransactionDB {
+    inner: *mut ffi::rocksdb_transactiondb_t,
+    cfs: BTreeMap<String, ColumnFamily>,
+    path: PathBuf,
+}
+
+unsafe impl Send for TransactionDB {}
+unsafe impl Sync for TransactionDB {}
+
+/// Options used when opening a `TransactionDB`.
+pub struct TransactionDBOptions {
+    inner: *mut ffi::rocksdb_transactiondb_options_t,
+}
+
+/// Options used when beginning a `Transaction` on a `TransactionDB`.
+pub struct TransactionOptions {
+    inner: *mut ffi::rocksdb_transaction_options_t,
+}
+
+/// A consistent view of a `TransactionDB` at the point of creation, released
+/// when dropped.
+///
+/// ```
+/// use rocksdb::{Options, TransactionDB};
+///
+/// let path = "_path_for_transaction_db_snapshot";
+/// {
+///     let db = TransactionDB::open_default(path).unwrap();
+///     db.put(b"my key", b"old value").unwrap();
+///     let snapshot = db.snapshot();
+///     db.put(b"my key", b"new value").unwrap();
+///     assert_eq!(snapshot.get(b"my key").unwrap().unwrap(), b"old value");
+/// }
+/// let _ = rocksdb::DB::destroy(&Options::default(), path);
+/// ```
+pub struct TransactionDBSnapshot<'a> {
+    db: &'a TransactionDB,
+    inner: *const ffi::rocksdb_snapshot_t,
+}
+
+// Safety note: auto-implementing Send on most db-related types is prevented by the inner FFI
+// pointer. In most cases, however, this pointer is Send-safe because it is never aliased and
+// rocksdb internally does not rely on thread-local information for its user-exposed types.
+unsafe impl Send for TransactionDBOptions {}
+unsafe impl Send for TransactionOptions {}
+unsafe impl<'a> Send for TransactionDBSnapshot<'a> {}
+
+// Sync is similarly safe for many types because they do not expose interior mutability, and their
+// use within the rocksdb library is generally behind a const reference
+unsafe impl Sync for TransactionDBOptions {}
+unsafe impl Sync for TransactionOptions {}
+unsafe impl<'a> Sync for TransactionDBSnapshot<'a> {}
+
+impl TransactionDB {
+    /// Open a transaction database with default options.
+    pub fn open_default<P: AsRef<Path>>(path: P) -> Result<TransactionDB, Error> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        TransactionDB::open(&opts, &TransactionDBOptions::default(), path)
+    }
+
+    /// Open a transaction database with the specified options.
+    pub fn open<P: AsRef<Path>>(
+        opts: &Options,
+        txn_db_opts: &TransactionDBOptions,
+        path: P,
+    ) -> Result<TransactionDB, Error> {
+        TransactionDB::open_cf(opts, txn_db_opts, path, None::<&str>)
+    }
+
+    /// Open a transaction database with the given database options and column family names.
+    ///
+    /// Column families opened using this function will be created with default `Options`.
+    pub fn open_cf<P, I, N>(
+        opts: &Options,
+        txn_db_opts: &TransactionDBOptions,
+        path: P,
+        cfs: I,
+    ) -> Result<TransactionDB, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = N>,
+        N: AsRef<str>,
+    {
+        let cfs = cfs
+            .into_iter()
+            .map(|name| ColumnFamilyDescriptor::new(name.as_ref(), Options::default()));
+
+        TransactionDB::open_cf_descriptors(opts, txn_db_opts, path, cfs)
+    }
+
+    /// Open a transaction database with the given database options and column family descriptors.
+    pub fn open_cf_descriptors<P, I>(
+        opts: &Options,
+        txn_db_opts: &TransactionDBOptions,
+        path: P,
+        cfs: I,
+    ) -> Result<TransactionDB, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = ColumnFamilyDescriptor>,
+    {
+        let cfs: Vec<_> = cfs.into_iter().collect();
+
+        let path = path.as_ref();
+        let cpath = match CString::new(path.to_string_lossy().as_bytes()) {
+            Ok(c) => c,
+            Err(_) => {
+                return Err(Error::new(
+                    "Failed to convert path to CString \
+                     when opening DB."
+                        .to_owned(),
+                ));
+            }
+        };
+
+        if let Err(e) = fs::create_dir_all(&path) {
+            return Err(Error::new(format!(
+                "Failed to create RocksDB directory: `{:?}`.",
+                e
+            )));
+        }
+
+        let (db, cf_map) = if cfs.is_empty() {
+            let db = unsafe {
+                ffi_try!(ffi::rocksdb_transactiondb_open(
+                    opts.inner,
+                    txn_db_opts.inner,
+                    cpath.as_ptr() as *const _,
+                ))
+            };
+            (db, BTreeMap::new())
+        } else {
+            open_cf_handles(
+                cfs,
+                |num_cfs, cfnames, cfopts, cfhandles| unsafe {
+                    Ok(ffi_try!(ffi::rocksdb_transactiondb_open_column_families(
+                        opts.inner,
+                        txn_db_opts.inner,
+                        cpath.as_ptr(),
+                        num_cfs,
+                        cfnames,
+                        cfopts,
+                        cfhandles,
+                    )))
+                },
+                ffi::rocksdb_transactiondb_close,
+            )?
+        };
+
+        if db.is_null() {
+            return Err(Error::new("Could not initialize database.".to_owned()));
+        }
+
+        Ok(TransactionDB {
+            inner: db,
+            cfs: cf_map,
+            path: path.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path.as_path()
+    }
+
+    /// Begins a new transaction with default options.
+    pub fn transaction(&self) -> Transaction {
+        self.transaction_opt(&WriteOptions::default(), &TransactionOptions::default())
+    }
+
+    /// Begins a new transaction with the given write and transaction options.
+    pub fn transaction_opt(
+        &self,
+        writeopts: &WriteOptions,
+        txn_opts: &TransactionOptions,
+    ) -> Transaction {
+        unsafe {
+            Transaction::from_inner(ffi::rocksdb_transaction_begin(
+                self.inner,
+                writeopts.inner,
+                txn_opts.inner,
+                ptr::null_mut(),
+            ))
+        }
+    }
+
+    /// Creates a snapshot of the current state of the database.
+    pub fn snapshot(&self) -> TransactionDBSnapshot {
+        let inner = unsafe { ffi::rocksdb_transactiondb_create_snapshot(self.inner) };
+        TransactionDBSnapshot { db: self, inner }
+    }
+
+    pub fn create_cf<N: AsRef<str>>(&mut self, name: N, opts: &Options) -> Result<(), Error> {
+        let cname = match CString::new(name.as_ref().as_bytes()) {
+            Ok(c) => c,
+            Err(_) => {
+                return Err(Error::new(
+                    "Failed to convert path to CString \
+                     when opening rocksdb"
+                        .to_owned(),
+                ));
+            }
+        };
+        unsafe {
+            let inner = ffi_try!(ffi::rocksdb_transactiondb_create_column_family(
+                self.inner,
+                opts.inner,
+                cname.as_ptr(),
+            ));
+
+            self.cfs
+                .insert(name.as_ref().to_string(), ColumnFamily { inner });
+        };
+        Ok(())
+    }
+
+    /// Drops a column family and destroys its handle.
+    pub fn drop_cf(&mut self, name: &str) -> Result<(), Error> {
+        let inner = match self.cfs.get(name) {
+            Some(cf) => cf.inner,
+            None => {
+                return Err(Error::new(format!("Invalid column family: {}", name)));
+            }
+        };
+        unsafe {
+            // The C API has no transaction database variant of this call. A
+            // `rocksdb_transactiondb_t` wraps a single `TransactionDB*` just like a
+            // `rocksdb_t` wraps a single `DB*`, and `TransactionDB` derives from
+            // `DB` through single inheritance, so the cast reaches the same
+            // virtual `DropColumnFamily`, which also releases the column family's
+            // lock table.
+            ffi_try!(ffi::rocksdb_drop_column_family(
+                self.inner as *mut ffi::rocksdb_t,
+                inner,
+            ));
+            ffi::rocksdb_column_family_handle_destroy(inner);
+        }
+        self.cfs.remove(name);
+        Ok(())
+    }
+
+    /// Return the underlying column family handle.
+    pub fn cf_handle(&self, name: &str) -> Option<&ColumnFamily> {
+        self.cfs.get(name)
+    }
+
+    pub fn get_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+        let mut val_len: size_t = 0;
+
+        unsafe {
+            let val = ffi_try!(ffi::rocksdb_transactiondb_get(
+                self.inner,
+                readopts.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Vec<u8>>, Error> {
+        self.get_opt(key, &ReadOptions::default())
+    }
+
+    pub fn get_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+        let mut val_len: size_t = 0;
+
+        unsafe {
+            let val = ffi_try!(ffi::rocksdb_transactiondb_get_cf(
+                self.inner,
+                readopts.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    pub fn get_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_cf_opt(cf, key, &ReadOptions::default())
+    }
+
+    pub fn put_opt<K, V>(&self, key: K, value: V, writeopts: &WriteOptions) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transactiondb_put(
+                self.inner,
+                writeopts.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn put_cf_opt<K, V>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        value: V,
+        writeopts: &WriteOptions,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transactiondb_put_cf(
+                self.inner,
+                writeopts.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn merge_opt<K, V>(&self, key: K, value: V, writeopts: &WriteOptions) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transactiondb_merge(
+                self.inner,
+                writeopts.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn merge_cf_opt<K, V>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        value: V,
+        writeopts: &WriteOptions,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transactiondb_merge_cf(
+                self.inner,
+                writeopts.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn delete_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        writeopts: &WriteOptions,
+    ) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transactiondb_delete(
+                self.inner,
+                writeopts.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn delete_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        writeopts: &WriteOptions,
+    ) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi_try!(ffi::rocksdb_transactiondb_delete_cf(
+                self.inner,
+                writeopts.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            ));
+            Ok(())
+        }
+    }
+
+    pub fn put<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.put_opt(key, value, &WriteOptions::default())
+    }
+
+    pub fn put_cf<K, V>(&self, cf: &ColumnFamily, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.put_cf_opt(cf, key, value, &WriteOptions::default())
+    }
+
+    pub fn merge<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.merge_opt(key, value, &WriteOptions::default())
+    }
+
+    pub fn merge_cf<K, V>(&self, cf: &ColumnFamily, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.merge_cf_opt(cf, key, value, &WriteOptions::default())
+    }
+
+    pub fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<(), Error> {
+        self.delete_opt(key, &WriteOptions::default())
+    }
+
+    pub fn delete_cf<K: AsRef<[u8]>>(&self, cf: &ColumnFamily, key: K) -> Result<(), Error> {
+        self.delete_cf_opt(cf, key, &WriteOptions::default())
+    }
+
+    pub fn write_opt(&self, batch: WriteBatch, writeopts: &WriteOptions) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_transactiondb_write(
+                self.inner,
+                writeopts.inner,
+                batch.inner,
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn write(&self, batch: WriteBatch) -> Result<(), Error> {
+        self.write_opt(batch, &WriteOptions::default())
+    }
+
+    pub fn iterator(&self, mode: IteratorMode) -> DBIterator {
+        self.iterator_opt(mode, ReadOptions::default())
+    }
+
+    pub fn iterator_opt(&self, mode: IteratorMode, readopts: ReadOptions) -> DBIterator {
+        DBIterator::from_raw(self.raw_iterator_opt(readopts), mode)
+    }
+
+    pub fn iterator_cf(
+        &self,
+        cf_handle: &ColumnFamily,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        self.iterator_cf_opt(cf_handle, ReadOptions::default(), mode)
+    }
+
+    pub fn iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        Ok(DBIterator::from_raw(
+            self.raw_iterator_cf_opt(cf_handle, readopts)?,
+            mode,
+        ))
+    }
+
+    /// Opens a raw iterator over the database, using the default read options
+    pub fn raw_iterator(&self) -> DBRawIterator {
+        self.raw_iterator_opt(ReadOptions::default())
+    }
+
+    /// Opens a raw iterator over the database, using the given read options
+    pub fn raw_iterator_opt(&self, readopts: ReadOptions) -> DBRawIterator {
+        unsafe {
+            let inner = ffi::rocksdb_transactiondb_create_iterator(self.inner, readopts.inner);
+            DBRawIterator::from_inner(inner, readopts)
+        }
+    }
+
+    /// Opens a raw iterator over the given column family, using the default read options
+    pub fn raw_iterator_cf(&self, cf_handle: &ColumnFamily) -> Result<DBRawIterator, Error> {
+        self.raw_iterator_cf_opt(cf_handle, ReadOptions::default())
+    }
+
+    /// Opens a raw iterator over the given column family, using the given read options
+    pub fn raw_iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+    ) -> Result<DBRawIterator, Error> {
+        unsafe {
+            let inner = ffi::rocksdb_transactiondb_create_iterator_cf(
+                self.inner,
+                readopts.inner,
+                cf_handle.inner,
+            );
+            Ok(DBRawIterator::from_inner(inner, readopts))
+        }
+    }
+}
+
+impl<'a> TransactionDBSnapshot<'a> {
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Vec<u8>>, Error> {
+        self.get_opt(key, ReadOptions::default())
+    }
+
+    pub fn get_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.set_snapshot(&mut readopts);
+        self.db.get_opt(key, &readopts)
+    }
+
+    pub fn get_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_cf_opt(cf, key, ReadOptions::default())
+    }
+
+    pub fn get_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.set_snapshot(&mut readopts);
+        self.db.get_cf_opt(cf, key, &readopts)
+    }
+
+    pub fn iterator(&self, mode: IteratorMode) -> DBIterator {
+        self.iterator_opt(mode, ReadOptions::default())
+    }
+
+    pub fn iterator_opt(&self, mode: IteratorMode, readopts: ReadOptions) -> DBIterator {
+        DBIterator::from_raw(self.raw_iterator_opt(readopts), mode)
+    }
+
+    pub fn iterator_cf(
+        &self,
+        cf_handle: &ColumnFamily,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        self.iterator_cf_opt(cf_handle, ReadOptions::default(), mode)
+    }
+
+    pub fn iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+        mode: IteratorMode,
+    ) -> Result<DBIterator, Error> {
+        Ok(DBIterator::from_raw(
+            self.raw_iterator_cf_opt(cf_handle, readopts)?,
+            mode,
+        ))
+    }
+
+    /// Opens a raw iterator over the data in this snapshot, using the default read options.
+    pub fn raw_iterator(&self) -> DBRawIterator {
+        self.raw_iterator_opt(ReadOptions::default())
+    }
+
+    /// Opens a raw iterator over the data in this snapshot, using the given read options.
+    pub fn raw_iterator_opt(&self, mut readopts: ReadOptions) -> DBRawIterator {
+        self.set_snapshot(&mut readopts);
+        self.db.raw_iterator_opt(readopts)
+    }
+
+    /// Opens a raw iterator over the data in this snapshot under the given column family,
+    /// using the default read options.
+    pub fn raw_iterator_cf(&self, cf_handle: &ColumnFamily) -> Result<DBRawIterator, Error> {
+        self.raw_iterator_cf_opt(cf_handle, ReadOptions::default())
+    }
+
+    /// Opens a raw iterator over the data in this snapshot under the given column family,
+    /// using the given read options.
+    pub fn raw_iterator_cf_opt(
+        &self,
+        cf_handle: &ColumnFamily,
+        mut readopts: ReadOptions,
+    ) -> Result<DBRawIterator, Error> {
+        self.set_snapshot(&mut readopts);
+        self.db.raw_iterator_cf_opt(cf_handle, readopts)
+    }
+
+    fn set_snapshot(&self, readopts: &mut ReadOptions) {
+        unsafe {
+            ffi::rocksdb_readoptions_set_snapshot(readopts.inner, self.inner);
+        }
+    }
+}
+
+impl<'a> Drop for TransactionDBSnapshot<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_transactiondb_release_snapshot(self.db.inner, self.inner);
+        }
+    }
+}
+
+impl Drop for TransactionDB {
+    fn drop(&mut self) {
+        unsafe {
+            for cf in self.cfs.values() {
+                ffi::rocksdb_column_family_handle_destroy(cf.inner);
+            }
+            ffi::rocksdb_transactiondb_close(self.inner);
+        }
+    }
+}
+
+impl fmt::Debug for TransactionDB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RocksDB TransactionDB {{ path: {:?} }}", self.path())
+    }
+}
+
+impl TransactionDBOptions {
+    /// Specifies the maximum number of keys that can be locked at the same time
+    /// per column family. If the number of locked keys is greater than this,
+    /// any attempt to lock another key fails.
+    ///
+    /// Default: `-1` (no limit)
+    pub fn set_max_num_locks(&mut self, max_num_locks: i64) {
+        unsafe {
+            ffi::rocksdb_transactiondb_options_set_max_num_locks(self.inner, max_num_locks);
+        }
+    }
+
+    /// Increasing the number of stripes in the lock table may reduce lock
+    /// contention at the cost of memory.
+    ///
+    /// Default: `16`
+    pub fn set_num_stripes(&mut self, num_stripes: usize) {
+        unsafe {
+            ffi::rocksdb_transactiondb_options_set_num_stripes(self.inner, num_stripes);
+        }
+    }
+
+    /// Default lock timeout in milliseconds for transactions that do not set
+    /// their own with `TransactionOptions::set_lock_timeout`. A negative value
+    /// means no timeout, which may lead to deadlocks.
+    ///
+    /// Default: `1000`
+    pub fn set_txn_lock_timeout(&mut self, millis: i64) {
+        unsafe {
+            ffi::rocksdb_transactiondb_options_set_transaction_lock_timeout(self.inner, millis);
+        }
+    }
+
+    /// Lock timeout in milliseconds used by writes made directly on the
+    /// `TransactionDB` rather than through a `Transaction`.
+    ///
+    /// Default: `1000`
+    pub fn set_default_lock_timeout(&mut self, millis: i64) {
+        unsafe {
+            ffi::rocksdb_transactiondb_options_set_default_lock_timeout(self.inner, millis);
+        }
+    }
+}
+
+impl Default for TransactionDBOptions {
+    fn default() -> TransactionDBOptions {
+        let txn_db_opts = unsafe { ffi::rocksdb_transactiondb_options_create() };
+        if txn_db_opts.is_null() {
+            panic!("Could not create RocksDB transaction db options");
+        }
+        TransactionDBOptions { inner: txn_db_opts }
+    }
+}
+
+impl Drop for TransactionDBOptions {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_transactiondb_options_destroy(self.inner);
+        }
+    }
+}
+
+impl TransactionOptions {
+    pub fn new() -> TransactionOptions {
+        TransactionOptions::default()
+    }
+
+    /// If true, the transaction takes a snapshot when it begins and fails to
+    /// commit if any key it wrote was changed by someone else after that point.
+    ///
+    /// Default: `false`
+    pub fn set_snapshot(&mut self, snapshot: bool) {
+        unsafe {
+            ffi::rocksdb_transaction_options_set_set_snapshot(self.inner, snapshot as c_uchar);
+        }
+    }
+
+    /// If true, waiting on a lock checks for deadlocks and fails with an error
+    /// instead of waiting for the lock timeout.
+    ///
+    /// Default: `false`
+    pub fn set_deadlock_detect(&mut self, deadlock_detect: bool) {
+        unsafe {
+            ffi::rocksdb_transaction_options_set_deadlock_detect(
+                self.inner,
+                deadlock_detect as c_uchar,
+            );
+        }
+    }
+
+    /// Number of milliseconds to wait for a lock before failing. A negative
+    /// value uses `TransactionDBOptions::set_txn_lock_timeout`.
+    ///
+    /// Default: `-1`
+    pub fn set_lock_timeout(&mut self, millis: i64) {
+        unsafe {
+            ffi::rocksdb_transaction_options_set_lock_timeout(self.inner, millis);
+        }
+    }
+
+    /// Number of milliseconds after which the transaction's locks may be stolen
+    /// by other writers and it can no longer be committed. A negative value
+    /// means the transaction never expires.
+    ///
+    /// Default: `-1`
+    pub fn set_expiration(&mut self, millis: i64) {
+        unsafe {
+            ffi::rocksdb_transaction_options_set_expiration(self.inner, millis);
+        }
+    }
+
+    /// Number of wait-for edges followed when looking for a deadlock.
+    ///
+    /// Default: `50`
+    pub fn set_deadlock_detect_depth(&mut self, depth: i64) {
+        unsafe {
+            ffi::rocksdb_transaction_options_set_deadlock_detect_depth(self.inner, depth);
+        }
+    }
+
+    /// Maximum number of bytes the transaction's write batch may grow to.
+    /// Zero means no limit.
+    ///
+    /// Default: `0`
+    pub fn set_max_write_batch_size(&mut self, size: usize) {
+        unsafe {
+            ffi::rocksdb_transaction_options_set_max_write_batch_size(self.inner, size);
+        }
+    }
+}
+
+impl Default for TransactionOptions {
+    fn default() -> TransactionOptions {
+        let txn_opts = unsafe { ffi::rocksdb_transaction_options_create() };
+        if txn_opts.is_null() {
+            panic!("Could not create RocksDB transaction options");
+        }
+        TransactionOptions { inner: txn_opts }
+    }
+}
+
+impl Drop for TransactionOptions {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_transaction_options_destroy(self.inner);
+        }
+    }
+}
@@ -0,0 +1,486 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Typed access to column families, available with the `typed` feature.
+//!
+//! A `TypedColumnFamily` encodes keys and values with a pair of `Codec`s, so
+//! that callers work with their own types instead of byte slices:
+//!
+//! * `Ordered` encodes keys implementing `OrderedKey` so that the byte order of
+//!   the encoded keys matches the order of the keys themselves, which makes
+//!   iteration, range and prefix scans behave as expected.
+//! * `Bincode` and `Json` encode any type implementing serde's `Serialize`
+//!   and `Deserialize`.
+//! * `Raw` stores `Vec<u8>` and `String` as is.
+//!
+//! ```
+//! use rocksdb::typed::{Bincode, Ordered, TypedColumnFamily};
+//! use rocksdb::{Options, DB};
+//!
+//! let path = "_path_for_typed_column_family";
+//! {
+//!     let mut opts = Options::default();
+//!     opts.create_if_missing(true);
+//!     opts.create_missing_column_families(true);
+//!     let db = DB::open_cf(&opts, path, &["users"]).unwrap();
+//!     let cf = db.cf_handle("users").unwrap();
+//!     let users: TypedColumnFamily<(u32, String), Vec<String>, Ordered, Bincode> =
+//!         TypedColumnFamily::new(&db, cf);
+//!
+//!     users.put(&(1, "alice".to_owned()), &vec!["admin".to_owned()]).unwrap();
+//!     users.put(&(2, "bob".to_owned()), &vec![]).unwrap();
+//!
+//!     let roles = users.get(&(1, "alice".to_owned())).unwrap().unwrap();
+//!     assert_eq!(roles, vec!["admin".to_owned()]);
+//!
+//!     let ids: Vec<u32> = users
+//!         .prefix_iter(&2u32)
+//!         .map(|entry| (entry.unwrap().0).0)
+//!         .collect();
+//!     assert_eq!(ids, vec![2]);
+//! }
+//! let _ = DB::destroy(&Options::default(), path);
+//! ```
+
+use bincode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+use {ColumnFamily, DBIterator, Direction, Error, IteratorMode, DB};
+
+use std::marker::PhantomData;
+
+type Codecs<K, V, KC, VC> = PhantomData<fn() -> (K, V, KC, VC)>;
+
+/// Converts values of type `T` to and from bytes.
+pub trait Codec<T> {
+    fn encode(value: &T) -> Result<Vec<u8>, Error>;
+
+    fn decode(bytes: &[u8]) -> Result<T, Error>;
+}
+
+/// Stores `Vec<u8>` and `String` values without any transformation.
+pub struct Raw;
+
+impl Codec<Vec<u8>> for Raw {
+    fn encode(value: &Vec<u8>) -> Result<Vec<u8>, Error> {
+        Ok(value.clone())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Codec<String> for Raw {
+    fn encode(value: &String) -> Result<Vec<u8>, Error> {
+        Ok(value.as_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<String, Error> {
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| Error::new(format!("Failed to decode string: {}", e)))
+    }
+}
+
+/// Encodes values with bincode.
+///
+/// Bincode output does not preserve ordering, so this codec is best suited to values.
+pub struct Bincode;
+
+impl<T: Serialize + DeserializeOwned> Codec<T> for Bincode {
+    fn encode(value: &T) -> Result<Vec<u8>, Error> {
+        bincode::serialize(value)
+            .map_err(|e| Error::new(format!("Failed to encode with bincode: {}", e)))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, Error> {
+        bincode::deserialize(bytes)
+            .map_err(|e| Error::new(format!("Failed to decode with bincode: {}", e)))
+    }
+}
+
+/// Encodes values as JSON.
+///
+/// JSON output does not preserve ordering, so this codec is best suited to values.
+pub struct Json;
+
+impl<T: Serialize + DeserializeOwned> Codec<T> for Json {
+    fn encode(value: &T) -> Result<Vec<u8>, Error> {
+        serde_json::to_vec(value)
+            .map_err(|e| Error::new(format!("Failed to encode as JSON: {}", e)))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, Error> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| Error::new(format!("Failed to decode JSON: {}", e)))
+    }
+}
+
+/// Encodes keys implementing `OrderedKey`, preserving their order.
+pub struct Ordered;
+
+impl<T: OrderedKey> Codec<T> for Ordered {
+    fn encode(value: &T) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        value.encode_key(&mut out);
+        Ok(out)
+    }
+
+    fn decode(mut bytes: &[u8]) -> Result<T, Error> {
+        let key = T::decode_key(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(Error::new(
+                "Failed to decode key: trailing bytes.".to_owned(),
+            ));
+        }
+        Ok(key)
+    }
+}
+
+/// A type whose encoding sorts bytewise in the same order as its values.
+///
+/// Integers are encoded as fixed-width big-endian numbers, with the sign bit of
+/// signed integers flipped. Strings and byte vectors are escaped and terminated,
+/// so the encoding of a tuple is the concatenation of the encodings of its
+/// elements, and the encoding of a tuple's leading elements is a prefix of the
+/// encoding of the whole tuple.
+pub trait OrderedKey: Sized {
+    /// Appends the encoding of `self` to `out`.
+    fn encode_key(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value from the front of `input`, advancing it past the bytes read.
+    fn decode_key(input: &mut &[u8]) -> Result<Self, Error>;
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if input.len() < len {
+        return Err(Error::new(
+            "Failed to decode key: unexpected end of input.".to_owned(),
+        ));
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+macro_rules! ordered_unsigned {
+    ($($t:ty),*) => {$(
+        impl OrderedKey for $t {
+            fn encode_key(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_be_bytes());
+            }
+
+            fn decode_key(input: &mut &[u8]) -> Result<$t, Error> {
+                let mut bytes = [0; std::mem::size_of::<$t>()];
+                let len = bytes.len();
+                bytes.copy_from_slice(take(input, len)?);
+                Ok(<$t>::from_be_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+macro_rules! ordered_signed {
+    ($($t:ty => $u:ty),*) => {$(
+        impl OrderedKey for $t {
+            fn encode_key(&self, out: &mut Vec<u8>) {
+                let flipped = (*self as $u) ^ (1 << (std::mem::size_of::<$u>() * 8 - 1));
+                flipped.encode_key(out);
+            }
+
+            fn decode_key(input: &mut &[u8]) -> Result<$t, Error> {
+                let flipped = <$u>::decode_key(input)?;
+                Ok((flipped ^ (1 << (std::mem::size_of::<$u>() * 8 - 1))) as $t)
+            }
+        }
+    )*};
+}
+
+ordered_unsigned!(u8, u16, u32, u64, u128);
+ordered_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128);
+
+impl OrderedKey for bool {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn decode_key(input: &mut &[u8]) -> Result<bool, Error> {
+        match take(input, 1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(Error::new(format!(
+                "Failed to decode key: invalid bool {}.",
+                b
+            ))),
+        }
+    }
+}
+
+// Zero bytes are escaped as 0x00 0xff and the end is marked by 0x00 0x01, which sorts a
+// value before every longer value it is a prefix of.
+impl OrderedKey for Vec<u8> {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        for &b in self {
+            out.push(b);
+            if b == 0 {
+                out.push(0xff);
+            }
+        }
+        out.extend_from_slice(&[0, 1]);
+    }
+
+    fn decode_key(input: &mut &[u8]) -> Result<Vec<u8>, Error> {
+        let mut value = Vec::new();
+        loop {
+            let b = take(input, 1)?[0];
+            if b != 0 {
+                value.push(b);
+                continue;
+            }
+            match take(input, 1)?[0] {
+                0xff => value.push(0),
+                1 => return Ok(value),
+                b => {
+                    return Err(Error::new(format!(
+                        "Failed to decode key: invalid escape {}.",
+                        b
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl OrderedKey for String {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        self.as_bytes().to_vec().encode_key(out);
+    }
+
+    fn decode_key(input: &mut &[u8]) -> Result<String, Error> {
+        String::from_utf8(Vec::<u8>::decode_key(input)?)
+            .map_err(|e| Error::new(format!("Failed to decode key: {}", e)))
+    }
+}
+
+macro_rules! ordered_tuple {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($name: OrderedKey),*> OrderedKey for ($($name,)*) {
+            fn encode_key(&self, out: &mut Vec<u8>) {
+                let ($(ref $name,)*) = *self;
+                $($name.encode_key(out);)*
+            }
+
+            fn decode_key(input: &mut &[u8]) -> Result<Self, Error> {
+                Ok(($($name::decode_key(input)?,)*))
+            }
+        }
+    };
+}
+
+ordered_tuple!(A);
+ordered_tuple!(A, B);
+ordered_tuple!(A, B, C);
+ordered_tuple!(A, B, C, D);
+ordered_tuple!(A, B, C, D, E);
+
+/// A column family whose keys of type `K` and values of type `V` are encoded
+/// with the codecs `KC` and `VC`.
+pub struct TypedColumnFamily<'a, K, V, KC = Ordered, VC = Bincode> {
+    db: &'a DB,
+    cf: &'a ColumnFamily,
+    codecs: Codecs<K, V, KC, VC>,
+}
+
+impl<'a, K, V, KC, VC> TypedColumnFamily<'a, K, V, KC, VC>
+where
+    KC: Codec<K>,
+    VC: Codec<V>,
+{
+    pub fn new(db: &'a DB, cf: &'a ColumnFamily) -> TypedColumnFamily<'a, K, V, KC, VC> {
+        TypedColumnFamily {
+            db,
+            cf,
+            codecs: PhantomData,
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        match self.db.get_pinned_cf(self.cf, KC::encode(key)?)? {
+            Some(value) => VC::decode(&value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put(&self, key: &K, value: &V) -> Result<(), Error> {
+        self.db
+            .put_cf(self.cf, KC::encode(key)?, VC::encode(value)?)
+    }
+
+    pub fn delete(&self, key: &K) -> Result<(), Error> {
+        self.db.delete_cf(self.cf, KC::encode(key)?)
+    }
+
+    /// Iterates over all entries in key order.
+    pub fn iter(&self) -> TypedIterator<'a, K, V, KC, VC> {
+        self.iterator(IteratorMode::Start, None, None)
+    }
+
+    /// Iterates over the entries from `key` on, in the given direction.
+    pub fn iter_from(
+        &self,
+        key: &K,
+        direction: Direction,
+    ) -> Result<TypedIterator<'a, K, V, KC, VC>, Error> {
+        let key = KC::encode(key)?;
+        Ok(self.iterator(IteratorMode::From(&key, direction), None, None))
+    }
+
+    /// Iterates over the entries with keys from `start` (inclusive) to `end` (exclusive).
+    pub fn range(&self, start: &K, end: &K) -> Result<TypedIterator<'a, K, V, KC, VC>, Error> {
+        let start = KC::encode(start)?;
+        let end = KC::encode(end)?;
+        Ok(self.iterator(
+            IteratorMode::From(&start, Direction::Forward),
+            Some(end),
+            None,
+        ))
+    }
+
+    /// Iterates over the entries whose encoded key starts with the encoding of `prefix`.
+    ///
+    /// With the `Ordered` codec, `prefix` can be the leading elements of a tuple key.
+    pub fn prefix_iter<P>(&self, prefix: &P) -> TypedIterator<'a, K, V, KC, VC>
+    where
+        KC: Codec<P>,
+    {
+        match KC::encode(prefix) {
+            Ok(prefix) => self.iterator(
+                IteratorMode::From(&prefix, Direction::Forward),
+                None,
+                Some(prefix.clone()),
+            ),
+            Err(e) => TypedIterator {
+                inner: None,
+                upper_bound: None,
+                prefix: None,
+                error: Some(e),
+                codecs: PhantomData,
+            },
+        }
+    }
+
+    fn iterator(
+        &self,
+        mode: IteratorMode,
+        upper_bound: Option<Vec<u8>>,
+        prefix: Option<Vec<u8>>,
+    ) -> TypedIterator<'a, K, V, KC, VC> {
+        let (inner, error) = match self.db.iterator_cf(self.cf, mode) {
+            Ok(iter) => (Some(iter), None),
+            Err(e) => (None, Some(e)),
+        };
+        TypedIterator {
+            inner,
+            upper_bound,
+            prefix,
+            error,
+            codecs: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the decoded entries of a `TypedColumnFamily`.
+///
+/// Each entry is returned as a `Result`, since decoding may fail.
+pub struct TypedIterator<'a, K, V, KC, VC> {
+    inner: Option<DBIterator<'a>>,
+    upper_bound: Option<Vec<u8>>,
+    prefix: Option<Vec<u8>>,
+    error: Option<Error>,
+    codecs: Codecs<K, V, KC, VC>,
+}
+
+impl<'a, K, V, KC, VC> Iterator for TypedIterator<'a, K, V, KC, VC>
+where
+    KC: Codec<K>,
+    VC: Codec<V>,
+{
+    type Item = Result<(K, V), Error>;
+
+    fn next(&mut self) -> Option<Result<(K, V), Error>> {
+        if let Some(e) = self.error.take() {
+            self.inner = None;
+            return Some(Err(e));
+        }
+
+        let (key, value) = self.inner.as_mut()?.next()?;
+        let past_end = match self.upper_bound {
+            Some(ref end) => *key >= **end,
+            None => false,
+        };
+        let past_prefix = match self.prefix {
+            Some(ref prefix) => !key.starts_with(prefix),
+            None => false,
+        };
+        if past_end || past_prefix {
+            self.inner = None;
+            return None;
+        }
+
+        Some(KC::decode(&key).and_then(|k| VC::decode(&value).map(|v| (k, v))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Codec, Ordered};
+
+    fn encode<T>(value: T) -> Vec<u8>
+    where
+        Ordered: Codec<T>,
+    {
+        Ordered::encode(&value).unwrap()
+    }
+
+    #[test]
+    fn ordered_encoding_preserves_order() {
+        let ints = [i64::min_value(), -300, -1, 0, 1, 255, 256, i64::max_value()];
+        for pair in ints.windows(2) {
+            assert!(encode(pair[0]) < encode(pair[1]));
+        }
+
+        let strings = ["", "a", "a\u{0}", "a\u{0}b", "a\u{1}", "ab", "b"];
+        for pair in strings.windows(2) {
+            assert!(encode(pair[0].to_owned()) < encode(pair[1].to_owned()));
+        }
+
+        assert!(encode((1u32, "b".to_owned())) < encode((2u32, "a".to_owned())));
+        assert!(encode(("a".to_owned(), 2u8)) < encode(("ab".to_owned(), 1u8)));
+        assert!(encode((1u32, "x".to_owned())).starts_with(&encode(1u32)));
+    }
+
+    #[test]
+    fn ordered_encoding_roundtrip() {
+        let key = (-5i32, "a\u{0}b".to_owned(), vec![0u8, 0xff, 1], true);
+        let decoded: (i32, String, Vec<u8>, bool) = Ordered::decode(&encode(key.clone())).unwrap();
+        assert_eq!(decoded, key);
+
+        let mut bytes = encode(7u16);
+        bytes.push(0);
+        assert!(<Ordered as Codec<u16>>::decode(&bytes).is_err());
+    }
+}
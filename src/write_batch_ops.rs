@@ -0,0 +1,261 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Decoding of the records of a serialized `WriteBatch`.
+//!
+//! The format is the one written by RocksDB's `WriteBatch`: a 12-byte header
+//! holding the sequence number and the record count, followed by the records.
+//! Each record starts with a tag byte, followed by a varint32 column family id
+//! for the column family variants, followed by length-prefixed slices.
+
+use Error;
+
+// Record tags, from `ValueType` in RocksDB's `db/dbformat.h`.
+const TYPE_DELETION: u8 = 0x0;
+const TYPE_VALUE: u8 = 0x1;
+const TYPE_MERGE: u8 = 0x2;
+const TYPE_LOG_DATA: u8 = 0x3;
+const TYPE_CF_DELETION: u8 = 0x4;
+const TYPE_CF_VALUE: u8 = 0x5;
+const TYPE_CF_MERGE: u8 = 0x6;
+const TYPE_SINGLE_DELETION: u8 = 0x7;
+const TYPE_CF_SINGLE_DELETION: u8 = 0x8;
+const TYPE_BEGIN_PREPARE_XID: u8 = 0x9;
+const TYPE_END_PREPARE_XID: u8 = 0xA;
+const TYPE_COMMIT_XID: u8 = 0xB;
+const TYPE_ROLLBACK_XID: u8 = 0xC;
+const TYPE_NOOP: u8 = 0xD;
+const TYPE_CF_RANGE_DELETION: u8 = 0xE;
+const TYPE_RANGE_DELETION: u8 = 0xF;
+const TYPE_CF_BLOB_INDEX: u8 = 0x10;
+const TYPE_BLOB_INDEX: u8 = 0x11;
+const TYPE_BEGIN_PERSISTED_PREPARE_XID: u8 = 0x12;
+const TYPE_BEGIN_UNPREPARE_XID: u8 = 0x13;
+
+const HEADER_SIZE: usize = 12;
+
+/// A record of a `WriteBatch`, borrowing its keys and values from the batch.
+///
+/// `cf` is the id of the column family the operation applies to, `0` being
+/// the default column family.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WriteBatchOp<'a> {
+    Put {
+        cf: u32,
+        key: &'a [u8],
+        value: &'a [u8],
+    },
+    Delete {
+        cf: u32,
+        key: &'a [u8],
+    },
+    SingleDelete {
+        cf: u32,
+        key: &'a [u8],
+    },
+    /// Deletion of the keys from `begin_key` (inclusive) to `end_key` (exclusive).
+    DeleteRange {
+        cf: u32,
+        begin_key: &'a [u8],
+        end_key: &'a [u8],
+    },
+    Merge {
+        cf: u32,
+        key: &'a [u8],
+        value: &'a [u8],
+    },
+    /// A reference to a value stored by BlobDB.
+    PutBlobIndex {
+        cf: u32,
+        key: &'a [u8],
+        value: &'a [u8],
+    },
+    /// A blob added with `WriteBatch::put_log_data`, which is only written to the WAL.
+    LogData(&'a [u8]),
+    /// The start of the records of a prepared transaction.
+    BeginPrepare,
+    /// The end of the records of the prepared transaction with the given id.
+    EndPrepare(&'a [u8]),
+    /// The commit of the prepared transaction with the given id.
+    Commit(&'a [u8]),
+    /// The rollback of the prepared transaction with the given id.
+    Rollback(&'a [u8]),
+    Noop,
+}
+
+/// An iterator over the records of a `WriteBatch`, returned by `WriteBatch::ops`.
+///
+/// Iteration stops after the first error.
+pub struct WriteBatchOps<'a> {
+    input: &'a [u8],
+    failed: bool,
+}
+
+impl<'a> WriteBatchOps<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> WriteBatchOps<'a> {
+        WriteBatchOps {
+            input: data.get(HEADER_SIZE..).unwrap_or(&[]),
+            failed: false,
+        }
+    }
+
+    fn varint32(&mut self) -> Option<u32> {
+        let mut result: u32 = 0;
+        for (i, &byte) in self.input.iter().enumerate().take(5) {
+            result |= u32::from(byte & 0x7f) << (7 * i);
+            if byte & 0x80 == 0 {
+                self.input = &self.input[i + 1..];
+                return Some(result);
+            }
+        }
+        None
+    }
+
+    fn slice(&mut self) -> Option<&'a [u8]> {
+        let len = self.varint32()? as usize;
+        if self.input.len() < len {
+            return None;
+        }
+        let (slice, rest) = self.input.split_at(len);
+        self.input = rest;
+        Some(slice)
+    }
+
+    fn decode(&mut self, tag: u8) -> Option<WriteBatchOp<'a>> {
+        let cf = match tag {
+            TYPE_CF_VALUE
+            | TYPE_CF_DELETION
+            | TYPE_CF_SINGLE_DELETION
+            | TYPE_CF_RANGE_DELETION
+            | TYPE_CF_MERGE
+            | TYPE_CF_BLOB_INDEX => self.varint32()?,
+            _ => 0,
+        };
+
+        let op = match tag {
+            TYPE_VALUE | TYPE_CF_VALUE => WriteBatchOp::Put {
+                cf,
+                key: self.slice()?,
+                value: self.slice()?,
+            },
+            TYPE_DELETION | TYPE_CF_DELETION => WriteBatchOp::Delete {
+                cf,
+                key: self.slice()?,
+            },
+            TYPE_SINGLE_DELETION | TYPE_CF_SINGLE_DELETION => WriteBatchOp::SingleDelete {
+                cf,
+                key: self.slice()?,
+            },
+            TYPE_RANGE_DELETION | TYPE_CF_RANGE_DELETION => WriteBatchOp::DeleteRange {
+                cf,
+                begin_key: self.slice()?,
+                end_key: self.slice()?,
+            },
+            TYPE_MERGE | TYPE_CF_MERGE => WriteBatchOp::Merge {
+                cf,
+                key: self.slice()?,
+                value: self.slice()?,
+            },
+            TYPE_BLOB_INDEX | TYPE_CF_BLOB_INDEX => WriteBatchOp::PutBlobIndex {
+                cf,
+                key: self.slice()?,
+                value: self.slice()?,
+            },
+            TYPE_LOG_DATA => WriteBatchOp::LogData(self.slice()?),
+            TYPE_BEGIN_PREPARE_XID
+            | TYPE_BEGIN_PERSISTED_PREPARE_XID
+            | TYPE_BEGIN_UNPREPARE_XID => WriteBatchOp::BeginPrepare,
+            TYPE_END_PREPARE_XID => WriteBatchOp::EndPrepare(self.slice()?),
+            TYPE_COMMIT_XID => WriteBatchOp::Commit(self.slice()?),
+            TYPE_ROLLBACK_XID => WriteBatchOp::Rollback(self.slice()?),
+            TYPE_NOOP => WriteBatchOp::Noop,
+            _ => return None,
+        };
+        Some(op)
+    }
+}
+
+impl<'a> Iterator for WriteBatchOps<'a> {
+    type Item = Result<WriteBatchOp<'a>, Error>;
+
+    fn next(&mut self) -> Option<Result<WriteBatchOp<'a>, Error>> {
+        if self.failed || self.input.is_empty() {
+            return None;
+        }
+
+        let tag = self.input[0];
+        self.input = &self.input[1..];
+        match self.decode(tag) {
+            Some(op) => Some(Ok(op)),
+            None => {
+                self.failed = true;
+                Some(Err(Error::new(format!(
+                    "Corruption: bad WriteBatch record with tag {}",
+                    tag
+                ))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{WriteBatchOp, WriteBatchOps};
+    use ErrorKind;
+
+    #[test]
+    fn decode_records() {
+        let mut data = vec![0; 12];
+        // Put("k", "v") to the default column family.
+        data.extend_from_slice(&[0x1, 1, b'k', 1, b'v']);
+        // DeleteRange("a", "b") in column family 300, whose id takes two varint bytes.
+        data.extend_from_slice(&[0xE, 0xac, 0x02, 1, b'a', 1, b'b']);
+        data.extend_from_slice(&[0x3, 2, b'l', b'd']);
+
+        let ops: Vec<_> = WriteBatchOps::new(&data).map(Result::unwrap).collect();
+        assert_eq!(
+            ops,
+            vec![
+                WriteBatchOp::Put {
+                    cf: 0,
+                    key: b"k",
+                    value: b"v",
+                },
+                WriteBatchOp::DeleteRange {
+                    cf: 300,
+                    begin_key: b"a",
+                    end_key: b"b",
+                },
+                WriteBatchOp::LogData(b"ld"),
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_corrupted_records() {
+        let mut data = vec![0; 12];
+        data.extend_from_slice(&[0x1, 5, b'k']);
+        let mut ops = WriteBatchOps::new(&data);
+        assert_eq!(
+            ops.next().unwrap().unwrap_err().kind(),
+            ErrorKind::Corruption
+        );
+        assert!(ops.next().is_none());
+
+        let mut data = vec![0; 12];
+        data.push(0x7f);
+        assert!(WriteBatchOps::new(&data).next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,459 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use ffi;
+use ffi_util::raw_data;
+use write_batch_ops::WriteBatchOps;
+use {ColumnFamily, DBRawIterator, Error, Options, ReadOptions, DB};
+
+use libc::{c_char, c_uchar, size_t};
+use std::slice;
+
+/// A write batch that keeps an index of its updates, so that they can be read
+/// back before the batch is written to the database.
+///
+/// Reads can be served from the batch alone, or from the batch overlaid on the
+/// database, in which case the updates in the batch take precedence. The batch
+/// is written atomically with `DB::write_with_index`.
+///
+/// ```
+/// use rocksdb::{Options, WriteBatchWithIndex, DB};
+///
+/// let path = "_path_for_write_batch_with_index";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     db.put(b"k1", b"v1").unwrap();
+///
+///     let mut batch = WriteBatchWithIndex::default();
+///     batch.put(b"k2", b"v2").unwrap();
+///     assert_eq!(batch.get_from_batch_and_db(&db, b"k1").unwrap().unwrap(), b"v1");
+///     assert_eq!(batch.get_from_batch_and_db(&db, b"k2").unwrap().unwrap(), b"v2");
+///     assert!(db.get(b"k2").unwrap().is_none());
+///
+///     db.write_with_index(&batch).unwrap();
+///     assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v2");
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct WriteBatchWithIndex {
+    pub(crate) inner: *mut ffi::rocksdb_writebatch_wi_t,
+    overwrite_keys: bool,
+}
+
+// Safety note: auto-implementing Send on most db-related types is prevented by the inner FFI
+// pointer. In most cases, however, this pointer is Send-safe because it is never aliased and
+// rocksdb internally does not rely on thread-local information for its user-exposed types.
+unsafe impl Send for WriteBatchWithIndex {}
+
+// Sync is similarly safe for many types because they do not expose interior mutability, and their
+// use within the rocksdb library is generally behind a const reference
+unsafe impl Sync for WriteBatchWithIndex {}
+
+impl WriteBatchWithIndex {
+    /// Creates a batch, reserving `reserved_bytes` for its serialized
+    /// representation.
+    ///
+    /// If `overwrite_keys` is true, the index only keeps the latest update of
+    /// each key, which is required by `iterator_with_base`. Otherwise the index
+    /// keeps every update, and iterators over the batch alone see all of them.
+    pub fn new(reserved_bytes: usize, overwrite_keys: bool) -> WriteBatchWithIndex {
+        let batch =
+            unsafe { ffi::rocksdb_writebatch_wi_create(reserved_bytes, overwrite_keys as c_uchar) };
+        if batch.is_null() {
+            panic!("Could not create RocksDB write batch with index");
+        }
+        WriteBatchWithIndex {
+            inner: batch,
+            overwrite_keys,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { ffi::rocksdb_writebatch_wi_count(self.inner) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the serialized representation of the batch, from which a
+    /// `WriteBatch` can be rebuilt with `WriteBatch::from_data`.
+    pub fn data(&self) -> &[u8] {
+        unsafe {
+            let mut batch_size: size_t = 0;
+            let data = ffi::rocksdb_writebatch_wi_data(self.inner, &mut batch_size);
+            slice::from_raw_parts(data as *const u8, batch_size as usize)
+        }
+    }
+
+    /// Returns an iterator over the records of the batch.
+    pub fn ops(&self) -> WriteBatchOps {
+        WriteBatchOps::new(self.data())
+    }
+
+    pub fn put<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_wi_put(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    pub fn put_cf<K, V>(&mut self, cf: &ColumnFamily, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_wi_put_cf(
+                self.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    pub fn merge<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_wi_merge(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    pub fn merge_cf<K, V>(&mut self, cf: &ColumnFamily, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_wi_merge_cf(
+                self.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    pub fn delete<K: AsRef<[u8]>>(&mut self, key: K) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_wi_delete(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    pub fn delete_cf<K: AsRef<[u8]>>(&mut self, cf: &ColumnFamily, key: K) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_wi_delete_cf(
+                self.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    /// See `WriteBatch::single_delete`.
+    pub fn single_delete<K: AsRef<[u8]>>(&mut self, key: K) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_wi_singledelete(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    pub fn single_delete_cf<K: AsRef<[u8]>>(
+        &mut self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<(), Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_wi_singledelete_cf(
+                self.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    /// See `WriteBatch::put_log_data`.
+    pub fn put_log_data<V: AsRef<[u8]>>(&mut self, blob: V) -> Result<(), Error> {
+        let blob = blob.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_wi_put_log_data(
+                self.inner,
+                blob.as_ptr() as *const c_char,
+                blob.len() as size_t,
+            );
+            Ok(())
+        }
+    }
+
+    /// Records the current state of the batch, so that the updates added after
+    /// this point can be undone with `rollback_to_save_point`.
+    pub fn set_save_point(&mut self) {
+        unsafe {
+            ffi::rocksdb_writebatch_wi_set_save_point(self.inner);
+        }
+    }
+
+    /// Removes the updates added since the most recent save point, and removes
+    /// that save point. Returns an error if there is no save point.
+    pub fn rollback_to_save_point(&mut self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_writebatch_wi_rollback_to_save_point(
+                self.inner,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Clear all updates buffered in this batch.
+    pub fn clear(&mut self) -> Result<(), Error> {
+        unsafe {
+            ffi::rocksdb_writebatch_wi_clear(self.inner);
+        }
+        Ok(())
+    }
+
+    /// Returns the value of `key` in the default column family as updated by
+    /// this batch alone.
+    ///
+    /// Returns an error for which `Error::kind` is `ErrorKind::MergeInProgress`
+    /// if the batch holds merges for the key that cannot be resolved without
+    /// reading the database.
+    pub fn get_from_batch<K: AsRef<[u8]>>(
+        &self,
+        opts: &Options,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            let mut val_len: size_t = 0;
+            let val = ffi_try!(ffi::rocksdb_writebatch_wi_get_from_batch(
+                self.inner,
+                opts.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    pub fn get_from_batch_cf<K: AsRef<[u8]>>(
+        &self,
+        opts: &Options,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            let mut val_len: size_t = 0;
+            let val = ffi_try!(ffi::rocksdb_writebatch_wi_get_from_batch_cf(
+                self.inner,
+                opts.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    /// Returns the value of `key` in the default column family of `db`, as
+    /// updated by this batch.
+    ///
+    /// Use `Snapshot::get_from_batch` to read the database at a snapshot.
+    pub fn get_from_batch_and_db<K: AsRef<[u8]>>(
+        &self,
+        db: &DB,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_from_batch_and_db_opt(db, key, &ReadOptions::default())
+    }
+
+    pub fn get_from_batch_and_db_opt<K: AsRef<[u8]>>(
+        &self,
+        db: &DB,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            let mut val_len: size_t = 0;
+            let val = ffi_try!(ffi::rocksdb_writebatch_wi_get_from_batch_and_db(
+                self.inner,
+                db.inner,
+                readopts.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    pub fn get_from_batch_and_db_cf<K: AsRef<[u8]>>(
+        &self,
+        db: &DB,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_from_batch_and_db_cf_opt(db, cf, key, &ReadOptions::default())
+    }
+
+    pub fn get_from_batch_and_db_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        db: &DB,
+        cf: &ColumnFamily,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let key = key.as_ref();
+
+        unsafe {
+            let mut val_len: size_t = 0;
+            let val = ffi_try!(ffi::rocksdb_writebatch_wi_get_from_batch_and_db_cf(
+                self.inner,
+                db.inner,
+                readopts.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut val_len,
+            ));
+            Ok(raw_data(val, val_len))
+        }
+    }
+
+    /// Returns an iterator over the default column family of `db`, as updated
+    /// by this batch.
+    ///
+    /// Returns an error if the batch was not created with `overwrite_keys`.
+    pub fn iterator_with_base<'a>(
+        &'a self,
+        db: &'a DB,
+        readopts: ReadOptions,
+    ) -> Result<DBRawIterator<'a>, Error> {
+        self.check_overwrite_keys()?;
+        unsafe {
+            let base = ffi::rocksdb_create_iterator(db.inner, readopts.inner);
+            let inner = ffi::rocksdb_writebatch_wi_create_iterator_with_base(self.inner, base);
+            Ok(DBRawIterator::from_inner(inner, readopts))
+        }
+    }
+
+    pub fn iterator_with_base_cf<'a>(
+        &'a self,
+        db: &'a DB,
+        cf: &ColumnFamily,
+        readopts: ReadOptions,
+    ) -> Result<DBRawIterator<'a>, Error> {
+        self.check_overwrite_keys()?;
+        unsafe {
+            let base = ffi::rocksdb_create_iterator_cf(db.inner, readopts.inner, cf.inner);
+            let inner =
+                ffi::rocksdb_writebatch_wi_create_iterator_with_base_cf(self.inner, base, cf.inner);
+            Ok(DBRawIterator::from_inner(inner, readopts))
+        }
+    }
+
+    fn check_overwrite_keys(&self) -> Result<(), Error> {
+        if self.overwrite_keys {
+            Ok(())
+        } else {
+            Err(Error::new(
+                "Not implemented: iterators with a base require a batch created with \
+                 overwrite_keys"
+                    .to_owned(),
+            ))
+        }
+    }
+}
+
+impl Default for WriteBatchWithIndex {
+    /// Creates a batch that keeps only the latest update of each key.
+    fn default() -> WriteBatchWithIndex {
+        WriteBatchWithIndex::new(0, true)
+    }
+}
+
+impl Drop for WriteBatchWithIndex {
+    fn drop(&mut self) {
+        unsafe { ffi::rocksdb_writebatch_wi_destroy(self.inner) }
+    }
+}
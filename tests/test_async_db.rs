@@ -0,0 +1,213 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+#![cfg(feature = "async")]
+
+extern crate futures_core;
+extern crate rocksdb;
+mod util;
+
+use futures_core::Stream;
+use rocksdb::{
+    AsyncDB, AsyncDBIterator, BlockingPool, Direction, Error, IteratorMode, Options, OwnedSnapshot,
+    ReadOptions, WriteBatch, DB,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use util::DBPath;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+// A minimal executor, so that the tests do not depend on a particular runtime.
+fn block_on<F: Future>(mut future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+// Polls the next item of a stream through the `Stream` trait rather than the inherent methods.
+struct StreamNext<'a, S: 'a>(&'a mut S);
+
+impl<'a, S: Stream + Unpin> Future for StreamNext<'a, S> {
+    type Output = Option<S::Item>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        Stream::poll_next(Pin::new(&mut *self.0), cx)
+    }
+}
+
+#[test]
+fn async_db_operations() {
+    let path = DBPath::new("_rust_rocksdb_async_db_operations");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        let db = AsyncDB::new(Arc::new(db), BlockingPool::new(2));
+
+        block_on(db.put(b"k1".to_vec(), b"v1".to_vec())).unwrap();
+        block_on(db.put_cf("cf1", b"k1".to_vec(), b"cf_v1".to_vec())).unwrap();
+        assert_eq!(block_on(db.get(b"k1".to_vec())).unwrap().unwrap(), b"v1");
+        assert_eq!(
+            block_on(db.get_cf("cf1", b"k1".to_vec())).unwrap().unwrap(),
+            b"cf_v1"
+        );
+        assert!(block_on(db.get_cf("missing", b"k1".to_vec())).is_err());
+
+        let mut batch = WriteBatch::default();
+        batch.put(b"k2", b"v2").unwrap();
+        batch.delete(b"k1").unwrap();
+        block_on(db.write(batch)).unwrap();
+
+        let values = block_on(db.multi_get(vec![b"k1".to_vec(), b"k2".to_vec()])).unwrap();
+        assert!(values[0].as_ref().unwrap().is_none());
+        assert_eq!(values[1].as_ref().unwrap().as_ref().unwrap(), b"v2");
+
+        block_on(db.delete_cf("cf1", b"k1".to_vec())).unwrap();
+        assert!(block_on(db.get_cf("cf1", b"k1".to_vec()))
+            .unwrap()
+            .is_none());
+
+        block_on(db.flush()).unwrap();
+        block_on(db.compact_range(None::<Vec<u8>>, None::<Vec<u8>>)).unwrap();
+        assert_eq!(db.db().get(b"k2").unwrap().unwrap(), b"v2");
+    }
+}
+
+#[test]
+fn async_db_iterator() {
+    let path = DBPath::new("_rust_rocksdb_async_db_iterator");
+    {
+        let db = Arc::new(DB::open_default(&path).unwrap());
+        for i in 0..10u8 {
+            db.put([i], [i * 2]).unwrap();
+        }
+        let db = AsyncDB::new(db, BlockingPool::new(1));
+
+        let mut iter = db.iterator(IteratorMode::Start, 3);
+        let mut keys = vec![];
+        while let Some(entry) = block_on(iter.next()) {
+            let (key, value) = entry.unwrap();
+            assert_eq!(value[0], key[0] * 2);
+            keys.push(key[0]);
+        }
+        assert_eq!(keys, (0..10).collect::<Vec<_>>());
+        assert!(block_on(iter.next()).is_none());
+
+        let mut iter = db.iterator(IteratorMode::From(&[4], Direction::Reverse), 2);
+        let mut keys = vec![];
+        while let Some(entry) = block_on(iter.next()) {
+            keys.push(entry.unwrap().0[0]);
+        }
+        assert_eq!(keys, vec![4, 3, 2, 1, 0]);
+    }
+}
+
+#[test]
+fn async_db_iterator_opt_and_cf() {
+    let path = DBPath::new("_rust_rocksdb_async_db_iterator_opt_and_cf");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = Arc::new(DB::open_cf(&opts, &path, &["cf1"]).unwrap());
+        let cf1 = db.cf_handle("cf1").unwrap();
+        for i in 0..10u8 {
+            db.put([i], [i]).unwrap();
+            db.put_cf(cf1, [i], [i]).unwrap();
+        }
+        let snapshot = OwnedSnapshot::new(db.clone());
+        db.put([10], [10]).unwrap();
+        let db = AsyncDB::new(db, BlockingPool::new(1));
+
+        let collect = |mut iter: AsyncDBIterator| {
+            let mut keys = vec![];
+            while let Some(entry) = block_on(iter.next()) {
+                keys.push(entry.unwrap().0[0]);
+            }
+            keys
+        };
+
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_range([2u8]..[5u8]);
+        let iter = db.iterator_opt(IteratorMode::Start, readopts, 2);
+        assert_eq!(collect(iter), vec![2, 3, 4]);
+
+        let mut readopts = ReadOptions::default();
+        readopts.set_owned_snapshot(&snapshot);
+        let iter = db.iterator_opt(IteratorMode::End, readopts, 4);
+        assert_eq!(collect(iter), (0..10).rev().collect::<Vec<_>>());
+
+        db.db()
+            .delete_cf(db.db().cf_handle("cf1").unwrap(), [0u8])
+            .unwrap();
+        let iter = db
+            .iterator_cf("cf1", IteratorMode::From(&[5], Direction::Forward), 3)
+            .unwrap();
+        assert_eq!(collect(iter), vec![5, 6, 7, 8, 9]);
+
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_upper_bound([3u8]);
+        let iter = db
+            .iterator_cf_opt("cf1", IteratorMode::Start, readopts, 1)
+            .unwrap();
+        assert_eq!(collect(iter), vec![1, 2]);
+
+        assert!(db.iterator_cf("missing", IteratorMode::Start, 1).is_err());
+    }
+}
+
+#[test]
+fn blocking_pool_survives_panicking_job() {
+    let pool = BlockingPool::new(1);
+    let failed = pool.spawn(|| -> Result<u32, Error> { panic!("job failure") });
+    let err = block_on(failed.unwrap()).unwrap_err();
+    assert!(err.to_string().contains("job failure"));
+    assert_eq!(block_on(pool.spawn(|| Ok(42)).unwrap()).unwrap(), 42);
+}
+
+#[test]
+fn async_db_iterator_stream() {
+    let path = DBPath::new("_rust_rocksdb_async_db_iterator_stream");
+    {
+        let db = Arc::new(DB::open_default(&path).unwrap());
+        for i in 0..5u8 {
+            db.put([i], [i]).unwrap();
+        }
+        let db = AsyncDB::new(db, BlockingPool::new(1));
+
+        let mut stream = db.iterator(IteratorMode::End, 2);
+        let mut keys = vec![];
+        while let Some(entry) = block_on(StreamNext(&mut stream)) {
+            keys.push(entry.unwrap().0[0]);
+        }
+        assert_eq!(keys, vec![4, 3, 2, 1, 0]);
+    }
+}
@@ -54,3 +54,41 @@ fn backup_restore() {
     assert!(DB::destroy(&opts, restore_path).is_ok());
     assert!(DB::destroy(&opts, path).is_ok());
 }
+
+#[test]
+fn backup_info_and_verify() {
+    let path = "_rust_rocksdb_backup_info_test";
+    let backup_path = "_rust_rocksdb_backup_info_backup_path";
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    {
+        let db = DB::open(&opts, path).unwrap();
+        let backup_opts = BackupEngineOptions::default();
+        let mut backup_engine = BackupEngine::open(&backup_opts, &backup_path).unwrap();
+        assert!(backup_engine.get_backup_info().is_empty());
+
+        db.put(b"k1", b"v1").unwrap();
+        backup_engine.create_new_backup_flush(&db, true).unwrap();
+        db.put(b"k2", b"v2").unwrap();
+        backup_engine.create_new_backup_flush(&db, false).unwrap();
+
+        let info = backup_engine.get_backup_info();
+        assert_eq!(info.len(), 2);
+        assert!(info[0].backup_id < info[1].backup_id);
+        assert!(info[0].timestamp > 0);
+        assert!(info[0].size > 0);
+        assert!(info[0].num_files > 0);
+
+        for backup in &info {
+            assert!(backup_engine.verify_backup(backup.backup_id).is_ok());
+        }
+        assert!(backup_engine.verify_backup(info[1].backup_id + 1).is_err());
+
+        backup_engine.purge_old_backups(1).unwrap();
+        let remaining = backup_engine.get_backup_info();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].backup_id, info[1].backup_id);
+    }
+    assert!(DB::destroy(&opts, path).is_ok());
+    let _ = std::fs::remove_dir_all(backup_path);
+}
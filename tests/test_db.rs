@@ -17,8 +17,12 @@ extern crate rocksdb;
 
 mod util;
 
-use rocksdb::{Error, IteratorMode, Options, Snapshot, WriteBatch, DB};
+use rocksdb::{
+    BottommostLevelCompaction, CompactOptions, Error, ErrorKind, IteratorMode, Options,
+    OwnedSnapshot, Range, ReadOptions, Snapshot, WriteBatch, WriteBatchOp, DB,
+};
 use std::sync::Arc;
+use std::time::Duration;
 use std::{mem, thread};
 use util::DBPath;
 
@@ -71,6 +75,7 @@ fn errors_do_stuff() {
             assert!(message.find("IO error:").is_some());
             assert!(message.find("_rust_rocksdb_error").is_some());
             assert!(message.find("/LOCK:").is_some());
+            assert_eq!(s.kind(), ErrorKind::IOError);
         }
         Ok(_) => panic!("should fail"),
     }
@@ -192,6 +197,47 @@ fn sync_snapshot_test() {
     assert!(handler_2.join().unwrap());
 }
 
+#[test]
+fn owned_snapshot_test() {
+    let path = DBPath::new("_rust_rocksdb_owned_snapshottest");
+    {
+        let db = Arc::new(DB::open_default(&path).unwrap());
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+
+        let snapshot = OwnedSnapshot::new(db.clone());
+        assert_eq!(snapshot.sequence_number(), Some(2));
+        db.put(b"k1", b"v11").unwrap();
+        db.put(b"k3", b"v3").unwrap();
+
+        let mut readopts = ReadOptions::default();
+        readopts.set_owned_snapshot(&snapshot);
+        assert_eq!(db.get_opt(b"k1", &readopts).unwrap().unwrap(), b"v1");
+        assert!(db.get_opt(b"k3", &readopts).unwrap().is_none());
+
+        let iter = snapshot.iterator(IteratorMode::Start);
+        let raw = snapshot.raw_iterator();
+        drop(snapshot);
+
+        let handler = thread::spawn(move || {
+            let keys: Vec<_> = iter.map(|(k, _)| k).collect();
+            assert_eq!(
+                keys,
+                vec![
+                    b"k1".to_vec().into_boxed_slice(),
+                    b"k2".to_vec().into_boxed_slice()
+                ]
+            );
+
+            let mut raw = raw;
+            raw.seek(b"k1");
+            assert_eq!(raw.value(), Some(&b"v1"[..]));
+        });
+        handler.join().unwrap();
+        assert_eq!(db.get_opt(b"k1", &readopts).unwrap().unwrap(), b"v1");
+    }
+}
+
 #[test]
 fn set_option_test() {
     let path = DBPath::new("_rust_rocksdb_set_optionstest");
@@ -340,3 +386,236 @@ fn test_get_updates_since_out_of_range() {
     let result = db.get_updates_since(1000);
     assert!(result.is_err());
 }
+
+#[test]
+fn multi_get() {
+    let path = DBPath::new("_rust_rocksdb_multi_get");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+        db.put_cf(cf1, b"k1", b"cf1_v1").unwrap();
+
+        let snapshot = db.snapshot();
+        db.delete(b"k2").unwrap();
+
+        let values: Vec<_> = db
+            .multi_get(&[b"k1", b"k2", b"k3"])
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(values, vec![Some(b"v1".to_vec()), None, None]);
+
+        let values: Vec<_> = db
+            .multi_get_cf(vec![
+                (cf1, b"k1"),
+                (db.cf_handle("default").unwrap(), b"k1"),
+            ])
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(values, vec![Some(b"cf1_v1".to_vec()), Some(b"v1".to_vec())]);
+
+        let values: Vec<_> = snapshot
+            .multi_get(&[b"k1", b"k2"])
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(values, vec![Some(b"v1".to_vec()), Some(b"v2".to_vec())]);
+    }
+}
+
+#[test]
+fn open_with_ttl() {
+    let path = DBPath::new("_rust_rocksdb_open_with_ttl");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let db = DB::open_with_ttl(&opts, &path, Duration::from_secs(1)).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+
+        thread::sleep(Duration::from_secs(2));
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+        assert!(db.get(b"k1").unwrap().is_none());
+    }
+
+    let path = DBPath::new("_rust_rocksdb_open_with_ttl_too_large");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        assert!(DB::open_with_ttl(&opts, &path, Duration::from_secs(u64::max_value())).is_err());
+    }
+}
+
+#[test]
+fn compact_range_opt() {
+    let path = DBPath::new("_rust_rocksdb_compact_range_opt");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put_cf(cf1, b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+
+        let mut compact_opts = CompactOptions::default();
+        compact_opts.set_exclusive_manual_compaction(true);
+        compact_opts.set_bottommost_level_compaction(BottommostLevelCompaction::Force);
+        compact_opts.set_change_level(true);
+        compact_opts.set_target_level(1);
+        db.compact_range_opt(None::<&[u8]>, None::<&[u8]>, &compact_opts);
+        db.compact_range_cf_opt(cf1, Some(b"k0"), Some(b"k2"), &compact_opts);
+
+        assert_eq!(
+            db.property_int_value("rocksdb.num-files-at-level1")
+                .unwrap(),
+            Some(1)
+        );
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+        assert_eq!(db.get_cf(cf1, b"k1").unwrap().unwrap(), b"v1");
+    }
+}
+
+#[test]
+fn live_files() {
+    let path = DBPath::new("_rust_rocksdb_live_files");
+    {
+        let db = DB::open_default(&path).unwrap();
+        assert!(db.live_files().unwrap().is_empty());
+
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k3", b"v3").unwrap();
+        db.delete(b"k2").unwrap();
+        db.flush().unwrap();
+
+        let files = db.live_files().unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].name.ends_with(".sst"));
+        assert_eq!(files[0].level, 0);
+        assert!(files[0].size > 0);
+        assert_eq!(files[0].start_key, Some(b"k1".to_vec()));
+        assert_eq!(files[0].end_key, Some(b"k3".to_vec()));
+        assert_eq!(files[0].num_entries, 3);
+        assert_eq!(files[0].num_deletions, 1);
+    }
+}
+
+#[test]
+fn delete_range_and_files_in_range() {
+    let path = DBPath::new("_rust_rocksdb_delete_range_and_files_in_range");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        for key in &[b"a1", b"a2", b"b1"] {
+            db.put_cf(cf1, key, b"value").unwrap();
+        }
+        db.delete_range_cf(cf1, b"a1", b"b1").unwrap();
+        assert!(db.get_cf(cf1, b"a1").unwrap().is_none());
+        assert!(db.get_cf(cf1, b"a2").unwrap().is_none());
+        assert!(db.get_cf(cf1, b"b1").unwrap().is_some());
+
+        // Only files below level 0 are deleted, so compact the flushed file first.
+        db.put(b"c1", b"value").unwrap();
+        db.put(b"c2", b"value").unwrap();
+        db.flush().unwrap();
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+        assert_eq!(db.live_files().unwrap().len(), 1);
+
+        db.delete_files_in_range(Some(b"c1"), Some(b"c2")).unwrap();
+        assert!(db.get(b"c1").unwrap().is_none());
+        db.delete_files_in_range_cf(cf1, None::<&[u8]>, None::<&[u8]>)
+            .unwrap();
+    }
+}
+
+#[test]
+fn get_approximate_sizes() {
+    let path = DBPath::new("_rust_rocksdb_get_approximate_sizes");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        let value = vec![b'x'; 1024];
+        for i in 0..1000u32 {
+            let key = format!("k{:04}", i);
+            db.put(&key, &value).unwrap();
+            db.put_cf(cf1, &key, &value).unwrap();
+        }
+        db.flush().unwrap();
+
+        let ranges = [
+            Range::new(b"k0000", b"k0500"),
+            Range::new(b"k0500", b"k1000"),
+            Range::new(b"x", b"z"),
+        ];
+        let sizes = db.get_approximate_sizes(&ranges);
+        assert_eq!(sizes.len(), 3);
+        assert!(sizes[0] > 0);
+        assert!(sizes[1] > 0);
+        assert_eq!(sizes[2], 0);
+
+        // The column family has not been flushed, so its data is only in memtables.
+        assert_eq!(db.get_approximate_sizes_cf(cf1, &ranges), vec![0, 0, 0]);
+    }
+}
+
+#[test]
+fn test_get_updates_since_ops() {
+    let path = DBPath::new("_rust_rocksdb_test_get_updates_since_ops");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        let mut batch = WriteBatch::default();
+        batch.put_cf(cf1, b"k1", b"v1").unwrap();
+        batch.delete_range_cf(cf1, b"k2", b"k3").unwrap();
+        batch.put(b"k1", b"v1").unwrap();
+        db.write(batch).unwrap();
+
+        let mut iter = db.get_updates_since(0).unwrap();
+        let (_, batch) = iter.next().unwrap();
+        let ops: Vec<_> = batch.ops().map(Result::unwrap).collect();
+        assert_eq!(ops.len(), 3);
+        let cf_id = match ops[0] {
+            WriteBatchOp::Put { cf, key, value } => {
+                assert_eq!((key, value), (&b"k1"[..], &b"v1"[..]));
+                cf
+            }
+            ref op => panic!("unexpected operation {:?}", op),
+        };
+        assert_ne!(cf_id, 0);
+        assert_eq!(
+            ops[1],
+            WriteBatchOp::DeleteRange {
+                cf: cf_id,
+                begin_key: b"k2",
+                end_key: b"k3",
+            }
+        );
+        assert_eq!(
+            ops[2],
+            WriteBatchOp::Put {
+                cf: 0,
+                key: b"k1",
+                value: b"v1",
+            }
+        );
+    }
+}
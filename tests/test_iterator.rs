@@ -15,7 +15,8 @@
 extern crate rocksdb;
 mod util;
 
-use rocksdb::{Direction, IteratorMode, MemtableFactory, Options, DB};
+use rocksdb::{Direction, IteratorMode, MemtableFactory, Options, ReadOptions, DB};
+use std::ops::Bound;
 use util::DBPath;
 
 fn cba(input: &[u8]) -> Box<[u8]> {
@@ -317,3 +318,57 @@ fn test_custom_iterator() {
     let opts = Options::default();
     assert!(DB::destroy(&opts, path).is_ok());
 }
+
+#[test]
+fn test_iterate_bounds() {
+    let path = DBPath::new("_rust_rocksdb_iterate_bounds_test");
+    {
+        let db = DB::open_default(&path).unwrap();
+        for key in &["a", "b", "c", "d", "e"] {
+            db.put(key, key).unwrap();
+        }
+
+        let iter = {
+            let lower = b"b".to_vec();
+            let mut readopts = ReadOptions::default();
+            readopts.set_iterate_lower_bound(&lower);
+            readopts.set_iterate_upper_bound(b"d");
+            db.iterator_opt(IteratorMode::Start, readopts)
+        };
+        let keys: Vec<_> = iter.map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![cba(b"b"), cba(b"c")]);
+
+        let keys: Vec<_> = db
+            .range_iterator("b"..="d", Direction::Forward)
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys, vec![cba(b"b"), cba(b"c"), cba(b"d")]);
+
+        let keys: Vec<_> = db
+            .range_iterator("b".."d", Direction::Reverse)
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys, vec![cba(b"c"), cba(b"b")]);
+
+        let keys: Vec<_> = db
+            .range_iterator::<&str, _>((Bound::Excluded("b"), Bound::Unbounded), Direction::Reverse)
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys, vec![cba(b"e"), cba(b"d"), cba(b"c")]);
+
+        let keys: Vec<_> = db
+            .range_iterator(..b"c".to_vec(), Direction::Forward)
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys, vec![cba(b"a"), cba(b"b")]);
+
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_range("b".."d");
+        readopts.set_iterate_range::<&str, _>(..);
+        let keys: Vec<_> = db
+            .iterator_opt(IteratorMode::Start, readopts)
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys.len(), 5);
+    }
+}
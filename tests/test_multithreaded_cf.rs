@@ -0,0 +1,93 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+#![cfg(feature = "multi-threaded-cf")]
+
+extern crate rocksdb;
+mod util;
+
+use rocksdb::{Options, DB};
+use std::sync::Arc;
+use std::thread;
+use util::DBPath;
+
+#[test]
+fn create_and_drop_cf_concurrently() {
+    let path = DBPath::new("_rust_rocksdb_create_and_drop_cf_concurrently");
+    {
+        let db = Arc::new(DB::open_default(&path).unwrap());
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let db = db.clone();
+                thread::spawn(move || {
+                    let name = format!("cf{}", i);
+                    db.create_cf(&name, &Options::default()).unwrap();
+                    let cf = db.cf_handle(&name).unwrap();
+                    db.put_cf(cf, b"k1", name.as_bytes()).unwrap();
+                    assert_eq!(db.get_cf(cf, b"k1").unwrap().unwrap(), name.as_bytes());
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        for i in 0..4 {
+            assert!(db.cf_handle(&format!("cf{}", i)).is_some());
+        }
+    }
+}
+
+#[test]
+fn dropped_cf_handle_stays_valid() {
+    let path = DBPath::new("_rust_rocksdb_dropped_cf_handle_stays_valid");
+    {
+        let db = DB::open_default(&path).unwrap();
+        db.create_cf("cf1", &Options::default()).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        db.put_cf(cf1, b"k1", b"v1").unwrap();
+
+        db.drop_cf("cf1").unwrap();
+        assert!(db.cf_handle("cf1").is_none());
+        assert!(db.drop_cf("cf1").is_err());
+
+        // The handle is still valid, although the column family is gone.
+        let _ = db.get_cf(cf1, b"k1");
+    }
+}
+
+#[test]
+fn dropped_bound_cf_handle_stays_valid() {
+    let path = DBPath::new("_rust_rocksdb_dropped_bound_cf_handle_stays_valid");
+    {
+        let db = DB::open_default(&path).unwrap();
+        db.create_cf("cf1", &Options::default()).unwrap();
+        let cf1 = db.bound_cf_handle("cf1").unwrap();
+        let cf1_clone = cf1.clone();
+        db.put_cf(&cf1, b"k1", b"v1").unwrap();
+
+        db.drop_cf("cf1").unwrap();
+        assert!(db.bound_cf_handle("cf1").is_none());
+
+        // The handle stays valid until its last clone is dropped, which releases it.
+        drop(cf1);
+        let _ = db.get_cf(&cf1_clone, b"k1");
+        drop(cf1_clone);
+
+        db.create_cf("cf1", &Options::default()).unwrap();
+        let cf1 = db.bound_cf_handle("cf1").unwrap();
+        assert!(db.get_cf(&cf1, b"k1").unwrap().is_none());
+    }
+}
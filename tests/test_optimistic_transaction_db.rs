@@ -0,0 +1,80 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+extern crate rocksdb;
+mod util;
+
+use rocksdb::{
+    IteratorMode, OptimisticTransactionDB, OptimisticTransactionOptions, Options, WriteOptions,
+};
+use util::DBPath;
+
+#[test]
+fn optimistic_transaction_commit() {
+    let path = DBPath::new("_rust_rocksdb_optimistic_transaction_commit");
+    {
+        let db = OptimisticTransactionDB::open_default(&path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+
+        let txn = db.transaction();
+        assert_eq!(txn.get(b"k1").unwrap().unwrap(), b"v1");
+        txn.put(b"k2", b"v2").unwrap();
+        {
+            let keys: Vec<_> = txn.iterator(IteratorMode::Start).map(|(k, _)| k).collect();
+            assert_eq!(keys.len(), 2);
+        }
+        assert!(db.get(b"k2").unwrap().is_none());
+        txn.commit().unwrap();
+        assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v2");
+    }
+}
+
+#[test]
+fn optimistic_transaction_conflict() {
+    let path = DBPath::new("_rust_rocksdb_optimistic_transaction_conflict");
+    {
+        let db = OptimisticTransactionDB::open_default(&path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+
+        let txn = db.transaction();
+        assert_eq!(txn.get_for_update(b"k1", true).unwrap().unwrap(), b"v1");
+        txn.put(b"k1", b"txn").unwrap();
+
+        db.put(b"k1", b"outside").unwrap();
+
+        let err = txn.commit().unwrap_err();
+        assert!(err.is_busy());
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"outside");
+    }
+}
+
+#[test]
+fn optimistic_transaction_snapshot_conflict() {
+    let path = DBPath::new("_rust_rocksdb_optimistic_transaction_snapshot");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let db = OptimisticTransactionDB::open(&opts, &path).unwrap();
+
+        let mut txn_opts = OptimisticTransactionOptions::default();
+        txn_opts.set_snapshot(true);
+        let txn = db.transaction_opt(&WriteOptions::default(), &txn_opts);
+
+        db.put(b"k1", b"outside").unwrap();
+        txn.put(b"k1", b"txn").unwrap();
+
+        assert!(txn.commit().unwrap_err().is_busy());
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"outside");
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+extern crate rocksdb;
+mod util;
+
+use rocksdb::{IteratorMode, Options, ReadOnlyDB, SecondaryDB, DB};
+use std::fs;
+use util::DBPath;
+
+#[test]
+fn read_only_db() {
+    let path = DBPath::new("_rust_rocksdb_read_only_db");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put_cf(cf1, b"k2", b"v2").unwrap();
+
+        // The primary still holds the lock and has unflushed writes in its WAL.
+        assert!(ReadOnlyDB::open(&Options::default(), &path, true).is_err());
+
+        let read_only = ReadOnlyDB::open_cf(&Options::default(), &path, &["cf1"], false).unwrap();
+        let cf1 = read_only.cf_handle("cf1").unwrap();
+        assert_eq!(read_only.get(b"k1").unwrap().unwrap(), b"v1");
+        assert_eq!(read_only.get_cf(cf1, b"k2").unwrap().unwrap(), b"v2");
+
+        // Writes made after opening are not visible.
+        db.put(b"k3", b"v3").unwrap();
+        assert!(read_only.get(b"k3").unwrap().is_none());
+        assert_eq!(read_only.iterator(IteratorMode::Start).count(), 1);
+    }
+}
+
+#[test]
+fn read_only_db_missing() {
+    let path = DBPath::new("_rust_rocksdb_read_only_db_missing");
+    assert!(ReadOnlyDB::open(&Options::default(), &path, false).is_err());
+    // Opening must not have created the database directory.
+    assert!(fs::metadata(&path).is_err());
+    DB::open_default(&path).unwrap();
+}
+
+#[test]
+fn secondary_db() {
+    let path = DBPath::new("_rust_rocksdb_secondary_db");
+    let secondary_path = DBPath::new("_rust_rocksdb_secondary_db_secondary");
+    {
+        let db = DB::open_default(&path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+
+        let mut opts = Options::default();
+        opts.set_max_open_files(-1);
+        let secondary = SecondaryDB::open(&opts, &path, &secondary_path).unwrap();
+        assert_eq!(secondary.get(b"k1").unwrap().unwrap(), b"v1");
+
+        db.put(b"k2", b"v2").unwrap();
+        assert!(secondary.get(b"k2").unwrap().is_none());
+        secondary.try_catch_up_with_primary().unwrap();
+        assert_eq!(secondary.get(b"k2").unwrap().unwrap(), b"v2");
+    }
+}
@@ -15,7 +15,10 @@
 extern crate rocksdb;
 mod util;
 
-use rocksdb::{BlockBasedOptions, Options, ReadOptions, DB};
+use rocksdb::{
+    BlockBasedOptions, Cache, DBCompressionType, Histogram, Options, RateLimiter, ReadOptions,
+    Ticker, DB,
+};
 use std::{fs, io::Read as _};
 use util::DBPath;
 
@@ -88,3 +91,89 @@ fn test_read_options() {
     let mut read_opts = ReadOptions::default();
     read_opts.set_verify_checksums(false);
 }
+
+#[test]
+fn test_set_ratelimiter() {
+    let path1 = DBPath::new("_rust_rocksdb_test_set_ratelimiter1");
+    let path2 = DBPath::new("_rust_rocksdb_test_set_ratelimiter2");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.enable_statistics();
+        opts.set_compression_type(DBCompressionType::None);
+        opts.set_ratelimiter(RateLimiter::new(1 << 20, 100 * 1000, 10));
+
+        let db1 = DB::open(&opts, &path1).unwrap();
+        let db2 = DB::open(&opts, &path2).unwrap();
+        db1.put(b"k1", b"v1").unwrap();
+        db1.flush().unwrap();
+        let drains = opts
+            .get_ticker_count(Ticker::NumberRateLimiterDrains)
+            .unwrap();
+
+        // Flushing far more than one 100KB refill makes the second database
+        // wait on the shared limiter.
+        let value = vec![7u8; 16 << 10];
+        for i in 0..32u32 {
+            db2.put(i.to_be_bytes(), &value).unwrap();
+        }
+        db2.flush().unwrap();
+        assert!(
+            opts.get_ticker_count(Ticker::NumberRateLimiterDrains)
+                .unwrap()
+                > drains
+        );
+    }
+}
+
+#[test]
+fn test_shared_block_cache() {
+    let path1 = DBPath::new("_rust_rocksdb_test_shared_block_cache1");
+    let path2 = DBPath::new("_rust_rocksdb_test_shared_block_cache2");
+    {
+        let cache = Cache::new_lru_cache(1 << 20);
+
+        let mut block_opts = BlockBasedOptions::default();
+        block_opts.set_block_cache(&cache);
+
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_block_based_table_factory(&block_opts);
+
+        let db1 = DB::open(&opts, &path1).unwrap();
+        let db2 = DB::open(&opts, &path2).unwrap();
+        for db in &[&db1, &db2] {
+            db.put(b"k1", b"v1").unwrap();
+            db.flush().unwrap();
+        }
+
+        let usage = cache.usage();
+        db1.get(b"k1").unwrap();
+        assert!(cache.usage() > usage);
+        let usage = cache.usage();
+        db2.get(b"k1").unwrap();
+        assert!(cache.usage() > usage);
+
+        cache.set_capacity(0);
+        assert_eq!(cache.usage(), cache.pinned_usage());
+    }
+}
+
+#[test]
+fn test_statistics_snapshot() {
+    let path = DBPath::new("_rust_rocksdb_test_statistics_snapshot");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.enable_statistics();
+
+        let db = DB::open(&opts, &path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.get(b"k1").unwrap();
+
+        let stats = opts.get_statistics_snapshot().unwrap();
+        assert_eq!(stats.ticker_count(Ticker::NumberKeysWritten), Some(1));
+        assert_eq!(stats.ticker_count(Ticker::NumberKeysRead), Some(1));
+        assert_eq!(stats.histogram_data(Histogram::DbGet).unwrap().count, 1);
+    }
+}
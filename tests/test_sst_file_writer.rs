@@ -0,0 +1,82 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+extern crate rocksdb;
+mod util;
+
+use rocksdb::{IngestExternalFileOptions, Options, SstFileWriter, DB};
+use std::fs;
+use util::DBPath;
+
+#[test]
+fn sst_file_writer_works() {
+    let path = DBPath::new("_rust_rocksdb_sst_file_writer");
+    let sst_path = "_rust_rocksdb_sst_file_writer.sst";
+    {
+        let opts = Options::default();
+        let mut writer = SstFileWriter::new(&opts);
+        writer.open(sst_path).unwrap();
+        assert_eq!(writer.num_entries(), 0);
+        assert!(writer.smallest_key().is_none());
+
+        writer.put(b"k1", b"v1").unwrap();
+        writer.put(b"k2", b"v2").unwrap();
+        writer.delete(b"k3").unwrap();
+        // Keys must be added in order.
+        assert!(writer.put(b"k0", b"v0").is_err());
+        writer.finish().unwrap();
+
+        assert_eq!(writer.smallest_key().unwrap(), b"k1");
+        assert_eq!(writer.largest_key().unwrap(), b"k3");
+        assert_eq!(writer.num_entries(), 3);
+        assert!(writer.file_size() > 0);
+
+        let db = DB::open_default(&path).unwrap();
+        db.put(b"k3", b"v3").unwrap();
+        db.ingest_external_file(&[sst_path]).unwrap();
+
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+        assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v2");
+        assert!(db.get(b"k3").unwrap().is_none());
+    }
+    fs::remove_file(sst_path).unwrap();
+}
+
+#[test]
+fn ingest_external_file_cf() {
+    let path = DBPath::new("_rust_rocksdb_ingest_external_file_cf");
+    let sst_path = "_rust_rocksdb_ingest_external_file_cf.sst";
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        let cf_opts = Options::default();
+        let mut writer = SstFileWriter::new(&cf_opts);
+        writer.open(sst_path).unwrap();
+        writer.put(b"k1", b"v1").unwrap();
+        writer.finish().unwrap();
+
+        let mut ingest_opts = IngestExternalFileOptions::default();
+        ingest_opts.set_move_files(true);
+        db.ingest_external_file_cf_opt(cf1, &[sst_path], &ingest_opts)
+            .unwrap();
+
+        assert_eq!(db.get_cf(cf1, b"k1").unwrap().unwrap(), b"v1");
+        assert!(db.get(b"k1").unwrap().is_none());
+    }
+    let _ = fs::remove_file(sst_path);
+}
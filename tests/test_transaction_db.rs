@@ -0,0 +1,188 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+extern crate rocksdb;
+mod util;
+
+use rocksdb::{
+    ColumnFamilyDescriptor, ErrorKind, ErrorSubCode, IteratorMode, Options, TransactionDB,
+    TransactionDBOptions, TransactionOptions, WriteOptions,
+};
+use util::DBPath;
+
+#[test]
+fn transaction_commit_and_rollback() {
+    let path = DBPath::new("_rust_rocksdb_transaction_commit_and_rollback");
+    {
+        let db = TransactionDB::open_default(&path).unwrap();
+
+        let txn = db.transaction();
+        txn.put(b"k1", b"v1").unwrap();
+        assert_eq!(txn.get(b"k1").unwrap().unwrap(), b"v1");
+        assert!(db.get(b"k1").unwrap().is_none());
+        txn.commit().unwrap();
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+
+        let txn = db.transaction();
+        txn.delete(b"k1").unwrap();
+        txn.put(b"k2", b"v2").unwrap();
+        txn.rollback().unwrap();
+        drop(txn);
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+        assert!(db.get(b"k2").unwrap().is_none());
+    }
+}
+
+#[test]
+fn transaction_savepoint() {
+    let path = DBPath::new("_rust_rocksdb_transaction_savepoint");
+    {
+        let db = TransactionDB::open_default(&path).unwrap();
+
+        let txn = db.transaction();
+        assert!(txn.rollback_to_savepoint().is_err());
+        txn.put(b"k1", b"v1").unwrap();
+        txn.set_savepoint();
+        txn.put(b"k2", b"v2").unwrap();
+        txn.rollback_to_savepoint().unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+        assert!(db.get(b"k2").unwrap().is_none());
+    }
+}
+
+#[test]
+fn transaction_get_for_update_locks_key() {
+    let path = DBPath::new("_rust_rocksdb_transaction_get_for_update");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let mut txn_db_opts = TransactionDBOptions::default();
+        txn_db_opts.set_txn_lock_timeout(10);
+        let db = TransactionDB::open(&opts, &txn_db_opts, &path).unwrap();
+        db.put(b"counter", b"0").unwrap();
+
+        let txn1 = db.transaction();
+        assert_eq!(
+            txn1.get_for_update(b"counter", true).unwrap().unwrap(),
+            b"0"
+        );
+
+        let mut txn_opts = TransactionOptions::default();
+        txn_opts.set_lock_timeout(10);
+        let txn2 = db.transaction_opt(&WriteOptions::default(), &txn_opts);
+        let err = txn2.get_for_update(b"counter", true).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+        assert_eq!(err.sub_code(), Some(ErrorSubCode::LockTimeout));
+        assert!(txn2.put(b"counter", b"2").is_err());
+
+        txn1.put(b"counter", b"1").unwrap();
+        txn1.commit().unwrap();
+
+        assert_eq!(
+            txn2.get_for_update(b"counter", true).unwrap().unwrap(),
+            b"1"
+        );
+        txn2.put(b"counter", b"2").unwrap();
+        txn2.commit().unwrap();
+        assert_eq!(db.get(b"counter").unwrap().unwrap(), b"2");
+    }
+}
+
+#[test]
+fn transaction_snapshots() {
+    let path = DBPath::new("_rust_rocksdb_transaction_snapshots");
+    {
+        let db = TransactionDB::open_default(&path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+
+        let snapshot = db.snapshot();
+        db.put(b"k1", b"v2").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+        assert_eq!(snapshot.get(b"k1").unwrap().unwrap(), b"v1");
+        assert_eq!(snapshot.iterator(IteratorMode::Start).count(), 1);
+        drop(snapshot);
+
+        let mut txn_opts = TransactionOptions::default();
+        txn_opts.set_snapshot(true);
+        let txn = db.transaction_opt(&WriteOptions::default(), &txn_opts);
+        db.put(b"k1", b"v3").unwrap();
+        txn.put(b"k2", b"txn").unwrap();
+
+        // Reads through the snapshot are repeatable but still see the transaction's writes.
+        let snapshot = txn.snapshot();
+        assert_eq!(snapshot.get(b"k1").unwrap().unwrap(), b"v2");
+        assert_eq!(snapshot.get(b"k2").unwrap().unwrap(), b"txn");
+        assert_eq!(txn.get(b"k1").unwrap().unwrap(), b"v3");
+        assert!(snapshot.get_for_update(b"k1", true).unwrap_err().is_busy());
+    }
+}
+
+#[test]
+fn transaction_column_families() {
+    let path = DBPath::new("_rust_rocksdb_transaction_column_families");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let cf = ColumnFamilyDescriptor::new("cf1", Options::default());
+        let db = TransactionDB::open_cf_descriptors(
+            &opts,
+            &TransactionDBOptions::default(),
+            &path,
+            vec![cf],
+        )
+        .unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        let txn = db.transaction();
+        txn.put_cf(cf1, b"k1", b"v1").unwrap();
+        txn.put(b"k2", b"v2").unwrap();
+        {
+            let keys: Vec<_> = txn
+                .iterator_cf(cf1, IteratorMode::Start)
+                .unwrap()
+                .map(|(k, _)| k)
+                .collect();
+            assert_eq!(keys, vec![b"k1".to_vec().into_boxed_slice()]);
+        }
+        txn.commit().unwrap();
+
+        assert_eq!(db.get_cf(cf1, b"k1").unwrap().unwrap(), b"v1");
+        assert!(db.get(b"k1").unwrap().is_none());
+        assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v2");
+    }
+}
+
+#[test]
+fn transaction_db_create_and_drop_cf() {
+    let path = DBPath::new("_rust_rocksdb_transaction_db_create_and_drop_cf");
+    {
+        let mut db = TransactionDB::open_default(&path).unwrap();
+        db.create_cf("cf1", &Options::default()).unwrap();
+        db.put_cf(db.cf_handle("cf1").unwrap(), b"k1", b"v1")
+            .unwrap();
+
+        db.drop_cf("cf1").unwrap();
+        assert!(db.cf_handle("cf1").is_none());
+        assert!(db.drop_cf("cf1").is_err());
+    }
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let cfs = rocksdb::DB::list_cf(&opts, &path).unwrap();
+        assert_eq!(cfs, vec!["default"]);
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+#![cfg(feature = "typed")]
+
+extern crate rocksdb;
+mod util;
+
+use rocksdb::typed::{Json, Ordered, Raw, TypedColumnFamily};
+use rocksdb::{Direction, Options, DB};
+use util::DBPath;
+
+#[test]
+fn typed_column_family_operations() {
+    let path = DBPath::new("_rust_rocksdb_typed_column_family_operations");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["scores"]).unwrap();
+        let cf = db.cf_handle("scores").unwrap();
+        let scores: TypedColumnFamily<i64, Vec<i64>, Ordered, Json> =
+            TypedColumnFamily::new(&db, cf);
+
+        for &key in &[3i64, -20, 0, 7, -1] {
+            scores.put(&key, &vec![key * 10]).unwrap();
+        }
+        assert_eq!(scores.get(&-20).unwrap().unwrap(), vec![-200]);
+        scores.delete(&0).unwrap();
+        assert!(scores.get(&0).unwrap().is_none());
+
+        let keys: Vec<i64> = scores.iter().map(|e| e.unwrap().0).collect();
+        assert_eq!(keys, vec![-20, -1, 3, 7]);
+
+        let keys: Vec<i64> = scores
+            .range(&-1, &7)
+            .unwrap()
+            .map(|e| e.unwrap().0)
+            .collect();
+        assert_eq!(keys, vec![-1, 3]);
+
+        let keys: Vec<i64> = scores
+            .iter_from(&2, Direction::Reverse)
+            .unwrap()
+            .map(|e| e.unwrap().0)
+            .collect();
+        assert_eq!(keys, vec![-1, -20]);
+
+        // The raw value stored under the key is JSON.
+        let raw: TypedColumnFamily<Vec<u8>, String, Raw, Raw> = TypedColumnFamily::new(&db, cf);
+        let (_, value) = raw.iter().next().unwrap().unwrap();
+        assert_eq!(value, "[-200]");
+    }
+}
+
+#[test]
+fn typed_prefix_iter() {
+    let path = DBPath::new("_rust_rocksdb_typed_prefix_iter");
+    {
+        let db = DB::open_default(&path).unwrap();
+        let cf = db.cf_handle("default").unwrap();
+        let events: TypedColumnFamily<(String, u64), String> = TypedColumnFamily::new(&db, cf);
+
+        events.put(&("a".to_owned(), 2), &"a2".to_owned()).unwrap();
+        events.put(&("a".to_owned(), 1), &"a1".to_owned()).unwrap();
+        events
+            .put(&("ab".to_owned(), 1), &"ab1".to_owned())
+            .unwrap();
+        events.put(&("b".to_owned(), 1), &"b1".to_owned()).unwrap();
+
+        let values: Vec<String> = events
+            .prefix_iter(&("a".to_owned(),))
+            .map(|e| e.unwrap().1)
+            .collect();
+        assert_eq!(values, vec!["a1".to_owned(), "a2".to_owned()]);
+    }
+}
@@ -13,8 +13,10 @@
 // limitations under the License.
 //
 extern crate rocksdb;
+mod util;
 
-use rocksdb::WriteBatch;
+use rocksdb::{Options, WriteBatch, WriteBatchIterator, WriteBatchOp, DB};
+use util::DBPath;
 
 #[test]
 fn test_write_batch_clear() {
@@ -25,3 +27,124 @@ fn test_write_batch_clear() {
     assert_eq!(batch.len(), 0);
     assert!(batch.is_empty());
 }
+
+#[test]
+fn test_write_batch_ops() {
+    let mut batch = WriteBatch::default();
+    batch.put(b"k1", b"v1").unwrap();
+    batch.merge(b"k1", b"m1").unwrap();
+    batch.delete(b"k2").unwrap();
+    batch.delete_range(b"k3", b"k5").unwrap();
+
+    let ops: Vec<_> = batch.ops().map(Result::unwrap).collect();
+    assert_eq!(
+        ops,
+        vec![
+            WriteBatchOp::Put {
+                cf: 0,
+                key: b"k1",
+                value: b"v1",
+            },
+            WriteBatchOp::Merge {
+                cf: 0,
+                key: b"k1",
+                value: b"m1",
+            },
+            WriteBatchOp::Delete { cf: 0, key: b"k2" },
+            WriteBatchOp::DeleteRange {
+                cf: 0,
+                begin_key: b"k3",
+                end_key: b"k5",
+            },
+        ]
+    );
+
+    assert_eq!(WriteBatch::default().ops().count(), 0);
+}
+
+struct Recorder(Vec<String>);
+
+impl WriteBatchIterator for Recorder {
+    fn put(&mut self, key: Box<[u8]>, _value: Box<[u8]>) {
+        self.0
+            .push(format!("put {}", String::from_utf8_lossy(&key)));
+    }
+    fn delete(&mut self, key: Box<[u8]>) {
+        self.0
+            .push(format!("delete {}", String::from_utf8_lossy(&key)));
+    }
+}
+
+#[test]
+fn test_write_batch_iterate() {
+    let mut batch = WriteBatch::default();
+    batch.delete_range(b"k0", b"k1").unwrap();
+    batch.merge(b"k1", b"m1").unwrap();
+    batch.put(b"k1", b"v1").unwrap();
+    batch.delete(b"k2").unwrap();
+
+    // Records the callbacks do not cover are skipped rather than ending the iteration.
+    let mut recorder = Recorder(vec![]);
+    batch.iterate(&mut recorder);
+    assert_eq!(recorder.0, vec!["put k1", "delete k2"]);
+}
+
+#[test]
+fn test_write_batch_data_roundtrip() {
+    let path = DBPath::new("_rust_rocksdb_write_batch_data_roundtrip");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        let mut batch = WriteBatch::default();
+        batch.put_cf(cf1, b"k1", b"v1").unwrap();
+        batch.merge(b"k1", b"m1").unwrap();
+        batch.single_delete(b"k2").unwrap();
+        batch.single_delete_cf(cf1, b"k3").unwrap();
+        batch.delete_range_cf(cf1, b"k4", b"k6").unwrap();
+        batch.put_log_data(b"blob").unwrap();
+
+        let copy = WriteBatch::from_data(batch.data()).unwrap();
+        assert_eq!(copy.data(), batch.data());
+        assert_eq!(copy.len(), 5);
+        let ops: Vec<_> = copy.ops().map(Result::unwrap).collect();
+        assert_eq!(ops, batch.ops().map(Result::unwrap).collect::<Vec<_>>());
+        assert_eq!(ops.len(), 6);
+        assert_eq!(ops[2], WriteBatchOp::SingleDelete { cf: 0, key: b"k2" });
+        assert_eq!(ops[5], WriteBatchOp::LogData(b"blob"));
+
+        let mut batch = WriteBatch::from_data(batch.data()).unwrap();
+        batch.clear().unwrap();
+        batch.put_cf(cf1, b"k1", b"v1").unwrap();
+        db.write(WriteBatch::from_data(batch.data()).unwrap())
+            .unwrap();
+        assert_eq!(db.get_cf(cf1, b"k1").unwrap().unwrap(), b"v1");
+
+        assert!(WriteBatch::from_data(b"short").is_err());
+        let mut corrupted = batch.data().to_vec();
+        corrupted.push(0x1);
+        assert!(WriteBatch::from_data(&corrupted).is_err());
+    }
+}
+
+#[test]
+fn test_write_batch_save_points() {
+    let mut batch = WriteBatch::default();
+    assert!(batch.rollback_to_save_point().is_err());
+    assert!(batch.pop_save_point().is_err());
+
+    batch.put(b"k1", b"v1").unwrap();
+    batch.set_save_point();
+    batch.put(b"k2", b"v2").unwrap();
+    batch.set_save_point();
+    batch.delete(b"k1").unwrap();
+    assert_eq!(batch.len(), 3);
+
+    batch.pop_save_point().unwrap();
+    batch.rollback_to_save_point().unwrap();
+    assert_eq!(batch.len(), 1);
+    assert!(batch.rollback_to_save_point().is_err());
+}
@@ -0,0 +1,113 @@
+// Copyright 2019 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+extern crate rocksdb;
+mod util;
+
+use rocksdb::{Options, ReadOptions, WriteBatch, WriteBatchWithIndex, DB};
+use util::DBPath;
+
+#[test]
+fn test_write_batch_with_index_get() {
+    let path = DBPath::new("_rust_rocksdb_wbwi_get");
+    let db = DB::open_default(&path).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.put(b"k2", b"v2").unwrap();
+    let snapshot = db.snapshot();
+    db.put(b"k3", b"v3").unwrap();
+
+    let mut batch = WriteBatchWithIndex::default();
+    batch.put(b"k1", b"b1").unwrap();
+    batch.delete(b"k2").unwrap();
+    batch.put(b"k4", b"b4").unwrap();
+    assert_eq!(batch.len(), 3);
+
+    let opts = Options::default();
+    assert_eq!(batch.get_from_batch(&opts, b"k1").unwrap().unwrap(), b"b1");
+    assert!(batch.get_from_batch(&opts, b"k2").unwrap().is_none());
+    assert!(batch.get_from_batch(&opts, b"k3").unwrap().is_none());
+
+    assert_eq!(
+        batch.get_from_batch_and_db(&db, b"k1").unwrap().unwrap(),
+        b"b1"
+    );
+    assert!(batch.get_from_batch_and_db(&db, b"k2").unwrap().is_none());
+    assert_eq!(
+        batch.get_from_batch_and_db(&db, b"k3").unwrap().unwrap(),
+        b"v3"
+    );
+    assert!(snapshot.get_from_batch(&batch, b"k3").unwrap().is_none());
+    assert_eq!(
+        snapshot.get_from_batch(&batch, b"k4").unwrap().unwrap(),
+        b"b4"
+    );
+
+    db.write_with_index(&batch).unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), b"b1");
+    assert!(db.get(b"k2").unwrap().is_none());
+    assert_eq!(db.get(b"k4").unwrap().unwrap(), b"b4");
+}
+
+#[test]
+fn test_write_batch_with_index_iterator_with_base() {
+    let path = DBPath::new("_rust_rocksdb_wbwi_iterator_with_base");
+    let db = DB::open_default(&path).unwrap();
+    db.put(b"a", b"1").unwrap();
+    db.put(b"c", b"3").unwrap();
+    db.put(b"e", b"5").unwrap();
+
+    let mut batch = WriteBatchWithIndex::default();
+    batch.put(b"b", b"2").unwrap();
+    batch.put(b"c", b"33").unwrap();
+    batch.delete(b"e").unwrap();
+
+    let mut iter = batch
+        .iterator_with_base(&db, ReadOptions::default())
+        .unwrap();
+    iter.seek_to_first();
+    let mut entries = Vec::new();
+    while iter.valid() {
+        entries.push((iter.key().unwrap().to_vec(), iter.value().unwrap().to_vec()));
+        iter.next();
+    }
+    assert_eq!(
+        entries,
+        vec![
+            (b"a".to_vec(), b"1".to_vec()),
+            (b"b".to_vec(), b"2".to_vec()),
+            (b"c".to_vec(), b"33".to_vec()),
+        ]
+    );
+
+    let unindexed = WriteBatchWithIndex::new(0, false);
+    assert!(unindexed
+        .iterator_with_base(&db, ReadOptions::default())
+        .is_err());
+}
+
+#[test]
+fn test_write_batch_with_index_save_point() {
+    let mut batch = WriteBatchWithIndex::default();
+    batch.put(b"k1", b"v1").unwrap();
+    batch.set_save_point();
+    batch.put(b"k2", b"v2").unwrap();
+    batch.rollback_to_save_point().unwrap();
+    assert!(batch.rollback_to_save_point().is_err());
+
+    let opts = Options::default();
+    assert!(batch.get_from_batch(&opts, b"k2").unwrap().is_none());
+
+    let copy = WriteBatch::from_data(batch.data()).unwrap();
+    assert_eq!(copy.len(), 1);
+}